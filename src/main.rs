@@ -69,11 +69,134 @@ fn main() {
         }
         ("sql", Some(matches)) => {
             // Source directory should be the output directory of the "fedora" sub command.
-            let (source_directory, output_directory) = get_sql_subcommand_args(matches);
-            sql::generate_sql(source_directory, output_directory);
+            let (source_directory, output_directory, mut config, dry_run, rollback, id_maps) =
+                get_sql_subcommand_args(matches);
+            // A zip archive is extracted once rather than by each of the steps below.
+            let input = match sql::InputDirectory::open(source_directory, &config) {
+                Ok(input) => input,
+                Err(error) => {
+                    Logger::error(&format!(
+                        "Failed to read '{}': {}",
+                        source_directory.display(),
+                        error
+                    ));
+                    std::process::exit(1);
+                }
+            };
+            let source_directory = input.path();
+            if let Err(error) = sql::valid_source_directory(source_directory, &config) {
+                Logger::error(&error);
+                std::process::exit(1);
+            }
+            // Both read the files again after they have been migrated.
+            if (rollback || id_maps) && config.file_names.reads_stdin() {
+                Logger::error("Can not generate the rollback or id maps of files read from stdin");
+                std::process::exit(1);
+            }
+            if dry_run {
+                match sql::validate_with_config(source_directory, &config) {
+                    Ok(report) => {
+                        for (file, rows) in &report.rows {
+                            info!("{}: {} rows", file, rows);
+                        }
+                        for reference in &report.unresolved {
+                            Logger::error(&reference.to_string());
+                        }
+                        for duplicate in &report.duplicates {
+                            Logger::error(&duplicate.to_string());
+                        }
+                        for warning in &report.warnings {
+                            warn!("{}", warning);
+                        }
+                        log_skipped(&report.skipped);
+                        if !report.is_valid() {
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(error) => {
+                        Logger::error(&format!("Failed to validate CSV files: {}", error));
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                #[cfg(feature = "mysql")]
+                if let Some(url) = matches.value_of("database_url") {
+                    match sql::database_offsets(url, &config.offsets) {
+                        Ok(offsets) => config.offsets = offsets,
+                        Err(error) => {
+                            Logger::error(&format!("Failed to read the offsets: {}", error));
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                let progress_bar = logger::progress_bar(0);
+                let bar = progress_bar.clone();
+                config.progress = Some(sql::Progress::new(move |_, processed, total| {
+                    bar.set_length(total as u64);
+                    bar.set_position(processed as u64);
+                }));
+                match sql::generate_sql_with_config(source_directory, output_directory, &config) {
+                    Ok(stats) => {
+                        progress_bar.finish_and_clear();
+                        for (entity, count) in &stats.entities {
+                            info!("{}: {} entities", entity, count);
+                        }
+                        for (table, rows) in &stats.tables {
+                            info!("{}: {} rows", table, rows);
+                        }
+                        for warning in &stats.warnings {
+                            warn!("{}", warning);
+                        }
+                        log_skipped(&stats.skipped);
+                    }
+                    Err(error) => {
+                        Logger::error(&format!("Failed to generate SQL: {}", error));
+                        std::process::exit(1);
+                    }
+                }
+                if rollback {
+                    if let Err(error) = sql::generate_rollback_with_config(
+                        source_directory,
+                        output_directory,
+                        &config,
+                    ) {
+                        Logger::error(&format!("Failed to generate rollback SQL: {}", error));
+                        std::process::exit(1);
+                    }
+                }
+                if id_maps {
+                    if let Err(error) = sql::generate_id_maps_with_config(
+                        source_directory,
+                        output_directory,
+                        &config,
+                    )
+                    .and_then(|_| {
+                        sql::generate_id_map_csvs_with_config(
+                            source_directory,
+                            output_directory,
+                            &config,
+                        )
+                    }) {
+                        Logger::error(&format!("Failed to generate id maps: {}", error));
+                        std::process::exit(1);
+                    }
+                }
+            }
         }
         _ => {
             args.print_long_help().unwrap();
         }
     }
 }
+
+fn log_skipped(skipped: &[sql::Error]) {
+    for error in skipped {
+        warn!("Skipped {}", error);
+    }
+    if !skipped.is_empty() {
+        warn!(
+            "Skipped {} rows which could not be parsed or duplicate an earlier row",
+            skipped.len()
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use crate::common::*;
+use std::fs;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+// The input directory, or the directory a zip archive of the input files was
+// extracted to, which is removed once dropped. The rows are read more than
+// once, e.g. media.csv is merged with media_revisions.csv, so the files are
+// extracted rather than read from within the archive.
+pub struct InputDirectory {
+    path: PathBuf,
+    _extracted: Option<tempfile::TempDir>,
+}
+
+impl InputDirectory {
+    // Archives are extracted to config.temp_directory if given.
+    pub fn open(input: &Path, config: &MigrationConfig) -> Result<Self> {
+        if !is_archive(input) {
+            return Ok(InputDirectory {
+                path: input.to_path_buf(),
+                _extracted: None,
+            });
+        }
+        let extracted = match &config.temp_directory {
+            Some(directory) => tempfile::tempdir_in(directory)?,
+            None => tempfile::tempdir()?,
+        };
+        extract(input, extracted.path(), &config.file_names)?;
+        Ok(InputDirectory {
+            path: extracted.path().to_path_buf(),
+            _extracted: Some(extracted),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+pub fn is_archive(input: &Path) -> bool {
+    input.is_file()
+        && input
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+fn invalid(error: zip::result::ZipError) -> Error {
+    Error::InvalidArchive(error.to_string())
+}
+
+// Only the entries named after one of the input files are extracted, wherever
+// they are within the archive, e.g. 'export/files.csv' or 'files.csv.gz'.
+fn extract(archive: &Path, dest: &Path, names: &FileNames) -> Result<()> {
+    let mut archive = ZipArchive::new(fs::File::open(archive)?).map_err(invalid)?;
+    let wanted = [
+        &names.users,
+        &names.files,
+        &names.media,
+        &names.media_revisions,
+        &names.nodes,
+    ];
+    let mut extracted = vec![];
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(invalid)?;
+        if entry.is_dir() {
+            continue;
+        }
+        // Names which would be outside of the archive are not input files.
+        let base = match entry.enclosed_name().as_deref().and_then(Path::file_name) {
+            Some(base) => base.to_string_lossy().to_string(),
+            None => continue,
+        };
+        let is_wanted = wanted
+            .iter()
+            .any(|wanted| base == wanted.as_str() || base == format!("{}.gz", wanted));
+        if !is_wanted {
+            continue;
+        }
+        if extracted.contains(&base) {
+            return Err(Error::InvalidArchive(format!(
+                "'{}' is in the archive more than once",
+                base
+            )));
+        }
+        let mut output = BufWriter::new(fs::File::create(dest.join(&base))?);
+        // The checksum is verified once the entry is read to the end.
+        std::io::copy(&mut entry, &mut output).map_err(|error| match error.kind() {
+            ErrorKind::InvalidData => {
+                Error::InvalidArchive(format!("'{}' is corrupt", entry.name()))
+            }
+            _ => error.into(),
+        })?;
+        output.flush()?;
+        extracted.push(base);
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::common::*;
+use crate::{
+    generate_rollback_with_config, generate_sql_string_with_config, generate_sql_to,
+    generate_sql_with_config, validate_with_config, ValidationReport,
+};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// The recommended way to generate the SQL, e.g.
+//
+//   MigrationBuilder::new(input)
+//       .node_type("my_type")
+//       .langcode("fr")
+//       .deterministic_uuids(true)
+//       .build()?
+//       .write_to(dest)?;
+//
+// Options without a setter can be given with config().
+pub struct MigrationBuilder {
+    input: PathBuf,
+    config: MigrationConfig,
+}
+
+impl MigrationBuilder {
+    pub fn new(input: impl Into<PathBuf>) -> Self {
+        MigrationBuilder {
+            input: input.into(),
+            config: MigrationConfig::default(),
+        }
+    }
+
+    // Replace all the options given so far.
+    pub fn config(mut self, config: MigrationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn node_type(mut self, node_type: &str) -> Self {
+        self.config.node_type = node_type.to_string();
+        self
+    }
+
+    pub fn langcode(mut self, langcode: &str) -> Self {
+        self.config.langcode = langcode.to_string();
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.config.batch_size = batch_size;
+        self
+    }
+
+    pub fn deterministic_uuids(mut self, deterministic_uuids: bool) -> Self {
+        self.config.deterministic_uuids = deterministic_uuids;
+        self
+    }
+
+    pub fn uuid_namespace(mut self, namespace: &str) -> Self {
+        self.config.uuid_namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn generated_at(mut self, timestamp: u64) -> Self {
+        self.config.generated_at = Some(timestamp);
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.config.dialect = dialect;
+        self
+    }
+
+    pub fn file_names(mut self, file_names: FileNames) -> Self {
+        self.config.file_names = file_names;
+        self
+    }
+
+    pub fn offsets(mut self, offsets: Offsets) -> Self {
+        self.config.offsets = offsets;
+        self
+    }
+
+    pub fn passwords(mut self, passwords: Passwords) -> Self {
+        self.config.passwords = passwords;
+        self
+    }
+
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.config.compress = compress;
+        self
+    }
+
+    pub fn split_output(mut self, split_output: bool) -> Self {
+        self.config.split_output = split_output;
+        self
+    }
+
+    pub fn progress(mut self, progress: Progress) -> Self {
+        self.config.progress = Some(progress);
+        self
+    }
+
+    // Fails if any of the options are invalid, the input is only read once
+    // the SQL is generated.
+    pub fn build(self) -> Result<Migration> {
+        self.config.validate()?;
+        Ok(Migration {
+            input: self.input,
+            config: self.config,
+        })
+    }
+}
+
+// A migration of the CSV files in the input directory with validated options.
+pub struct Migration {
+    input: PathBuf,
+    config: MigrationConfig,
+}
+
+impl Migration {
+    pub fn config(&self) -> &MigrationConfig {
+        &self.config
+    }
+
+    pub fn write_to(&self, dest: &Path) -> Result<Stats> {
+        generate_sql_with_config(&self.input, dest, &self.config)
+    }
+
+    pub fn write_rollback_to(&self, dest: &Path) -> Result<()> {
+        generate_rollback_with_config(&self.input, dest, &self.config)
+    }
+
+    pub fn write_sql_to(&self, writer: &mut dyn Write) -> Result<Stats> {
+        generate_sql_to(&self.input, writer, &self.config)
+    }
+
+    pub fn to_sql_string(&self) -> Result<String> {
+        generate_sql_string_with_config(&self.input, &self.config)
+    }
+
+    pub fn validate(&self) -> Result<ValidationReport> {
+        validate_with_config(&self.input, &self.config)
+    }
+}
@@ -0,0 +1,2340 @@
+use crate::files::*;
+pub(crate) use crate::hashing::{hash, serialize, source_ids_hash};
+use crate::media::*;
+use crate::nodes::*;
+use crate::users::*;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDateTime};
+use crypto::digest::Digest;
+use crypto::sha2::Sha512;
+use csv::ReaderBuilder;
+use flate2::read::GzDecoder;
+use indexmap::IndexMap; // Use instead of default HashMaps to preserver insertion order used to generate uid, fid, etc.
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::SystemTime;
+use uuid::Uuid;
+
+// Like PHP serialize(), but limited to an associative array of strings.
+// i.e. serialize(array("pid" => "a:1")); => a:1:{s:3:"pid";s:3:"a:1";}
+// Used to generate the source row hash for migrate map tables.
+fn serialize_map(values: &[(&str, &str)]) -> String {
+    let mut result = String::new();
+    result.push_str("a:");
+    result += &values.len().to_string();
+    result.push_str(":{");
+    values.iter().for_each(|(k, v)| {
+        for s in &[k, v] {
+            result.push_str("s:");
+            result += &s.len().to_string();
+            result.push_str(r#":""#);
+            result += s;
+            result.push_str(r#"";"#);
+        }
+    });
+    result.push('}');
+    result
+}
+
+// Quote the given value as a MySQL string literal, escaping the same characters
+// as mysql_real_escape_string() so arbitrary CSV values cannot break out of it.
+pub(crate) fn sql_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => result.push_str(r"\'"),
+            '\\' => result.push_str(r"\\"),
+            '\0' => result.push_str(r"\0"),
+            '\n' => result.push_str(r"\n"),
+            '\r' => result.push_str(r"\r"),
+            '\x1a' => result.push_str(r"\Z"),
+            _ => result.push(c),
+        }
+    }
+    result.push('\'');
+    result
+}
+
+// Like sql_string() but for SQLite, which only requires quotes to be doubled.
+fn sqlite_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+// Empty values of integer columns are NULL, as an empty literal is a syntax error.
+pub(crate) fn sql_nullable_int(value: &str) -> String {
+    if value.trim().is_empty() {
+        "NULL".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+// Types of the files typically found in Fedora, by extension.
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("aac", "audio/aac"),
+    ("gif", "image/gif"),
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("jp2", "image/jp2"),
+    ("jpeg", "image/jpeg"),
+    ("jpg", "image/jpeg"),
+    ("json", "application/json"),
+    ("m4a", "audio/mp4"),
+    ("mov", "video/quicktime"),
+    ("mp3", "audio/mpeg"),
+    ("mp4", "video/mp4"),
+    ("ogg", "audio/ogg"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("rdf", "application/rdf+xml"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    ("txt", "text/plain"),
+    ("wav", "audio/wav"),
+    ("xml", "application/xml"),
+    ("zip", "application/zip"),
+];
+
+// Guess the type of a file from its extension, if it is a common one.
+pub(crate) fn guess_mime_type(name: &str) -> Option<&'static str> {
+    let (_, extension) = name.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    MIME_TYPES
+        .iter()
+        .find(|(e, _)| *e == extension)
+        .map(|(_, mime_type)| *mime_type)
+}
+
+// Exports do not always include the type of files and media, in which case it
+// is guessed from the name, and otherwise left empty.
+pub(crate) fn mime_type_or_guess<'a>(mime_type: &'a str, name: &str) -> &'a str {
+    if mime_type.is_empty() {
+        guess_mime_type(name).unwrap_or_default()
+    } else {
+        mime_type
+    }
+}
+
+// The warning for a type the export left empty, if it did.
+pub(crate) fn mime_type_coercion(mime_type: &str, name: &str) -> Option<String> {
+    if !mime_type.is_empty() {
+        return None;
+    }
+    Some(match guess_mime_type(name) {
+        Some(mime_type) => format!("No mime type, guessed '{}' from the name", mime_type),
+        None => "No mime type, left empty".to_string(),
+    })
+}
+
+// Namespace of the deterministic (version 5) UUIDs unless the config gives one,
+// i.e. 5b9f6c8e-0a3d-4f2b-9e61-d7c4a2f013b8. Sites migrating the same
+// repository should each give their own so that their UUIDs do not collide.
+pub const UUID_NAMESPACE: Uuid = Uuid::from_u128(0x5b9f_6c8e_0a3d_4f2b_9e61_d7c4_a2f0_13b8);
+
+// Random UUIDs by default, otherwise derived from the entity type and source
+// ids so that repeated runs over the same input produce the same UUIDs.
+pub(crate) fn uuid(entity: &str, source_ids_hash: &str, config: &MigrationConfig) -> Uuid {
+    if config.deterministic_uuids {
+        let name = format!("{}:{}", entity, source_ids_hash);
+        Uuid::new_v5(&config.uuid_namespace(), name.as_bytes())
+    } else {
+        Uuid::new_v4()
+    }
+}
+
+// Alphabet of the custom base64 encoding used by phpass.
+const ITOA64: &[u8] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+// Log2 of the number of hash iterations, matches PhpassHashedPassword in Drupal 8.
+const PASSWORD_HASH_COUNT: u32 = 16;
+
+// Length of the hashes stored by Drupal, the encoded SHA-512 digest is truncated.
+const PASSWORD_HASH_LENGTH: usize = 55;
+
+// Port of PhpassHashedPassword::base64Encode().
+fn password_base64_encode(input: &[u8]) -> String {
+    let mut output = String::new();
+    let mut i = 0;
+    while i < input.len() {
+        let mut value = input[i] as usize;
+        i += 1;
+        output.push(ITOA64[value & 0x3f] as char);
+        if i < input.len() {
+            value |= (input[i] as usize) << 8;
+        }
+        output.push(ITOA64[(value >> 6) & 0x3f] as char);
+        if i >= input.len() {
+            break;
+        }
+        i += 1;
+        if i < input.len() {
+            value |= (input[i] as usize) << 16;
+        }
+        output.push(ITOA64[(value >> 12) & 0x3f] as char);
+        if i >= input.len() {
+            break;
+        }
+        i += 1;
+        output.push(ITOA64[(value >> 18) & 0x3f] as char);
+    }
+    output
+}
+
+// Like PhpassHashedPassword::hash(), produces a '$S$' hash of the password
+// which Drupal accepts at login, the salt is given so the output can be repeatable.
+pub(crate) fn password_hash(password: &str, salt: &[u8; 6]) -> String {
+    let mut setting = String::from("$S$");
+    setting.push(ITOA64[PASSWORD_HASH_COUNT as usize] as char);
+    setting += &password_base64_encode(salt);
+    let mut hasher = Sha512::new();
+    let mut hash = [0u8; 64];
+    hasher.input_str(&setting[4..12]);
+    hasher.input_str(password);
+    hasher.result(&mut hash);
+    for _ in 0..(1 << PASSWORD_HASH_COUNT) {
+        hasher.reset();
+        hasher.input(&hash);
+        hasher.input_str(password);
+        hasher.result(&mut hash);
+    }
+    let mut output = setting + &password_base64_encode(&hash);
+    output.truncate(PASSWORD_HASH_LENGTH);
+    output
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[derive(Debug)]
+pub enum Error {
+    CSVError(csv::Error),
+    IOError(std::io::Error),
+    Row {
+        file: PathBuf,
+        line: u64,
+        source: csv::Error,
+    }, // Could not deserialize the row at the given line of the source file.
+    JSONRow {
+        file: PathBuf,
+        line: u64,
+        source: serde_json::Error,
+    }, // Could not deserialize the row at the given line of the JSON Lines source file.
+    Config(String),         // Invalid options were given.
+    InvalidArchive(String), // The input zip archive could not be extracted.
+    ColumnCount {
+        table: String,
+        columns: usize,
+        values: usize,
+    }, // The rows of a table have more or fewer values than it has columns.
+    UnknownUser(String),    // A row refers to a user which is not in users.csv.
+    UnknownUsers(Vec<String>), // Rows refer to users which are not in users.csv.
+    OrphanedRevisions(Vec<(String, String)>), // Media revisions of media (pid, dsid) which are not in media.csv.
+    InconsistentId {
+        entity: &'static str,
+        hash: String,
+        existing: usize,
+        id: usize,
+    }, // Two tables sharing an id map gave the same row different ids.
+    UnknownMedia {
+        pid: String,
+        dsid: String,
+    }, // A media revision refers to media which is not in media.csv.
+    UnknownFile {
+        pid: String,
+        dsid: String,
+        version: String,
+    }, // A media revision refers to a file which is not in files.csv.
+    UnknownNode(String), // A node is a member of an object which is not in nodes.csv.
+    UnknownModel(String), // A node has a model which is not mapped to a taxonomy term.
+    UnknownMimeType(String), // A media without a bundle has a mime type which is not mapped to one.
+    MissingURL {
+        pid: String,
+        dsid: String,
+        version: String,
+    }, // A media of a remote bundle has no url.
+    UnknownState(String), // A node has a state which is not mapped to a moderation state.
+    InvalidWeight {
+        pid: String,
+        weight: String,
+    }, // A node has a weight which is not an integer.
+    InvalidFlag {
+        pid: String,
+        column: String,
+        value: String,
+    }, // A node has a promote or sticky value which is neither true nor false.
+    InvalidStatus {
+        name: String,
+        status: String,
+    }, // A user has a status which is neither blocked (0) nor active (1).
+    InvalidInteger {
+        entity: &'static str,
+        source_ids: Vec<String>,
+        column: &'static str,
+        value: String,
+    }, // A row has a value which is inserted into an integer column but is not an integer.
+    InvalidDate {
+        pid: String,
+        date: String,
+    }, // A row has a date which could not be parsed.
+    DuplicateSourceId {
+        file: String,
+        line: u64,
+        ids: Vec<String>,
+    }, // A row has the same source ids as an earlier row in the same file.
+    DuplicateMediaRevision {
+        file: String,
+        ids: Vec<String>,
+    }, // A row of media_revisions.csv has the same source ids as one of media.csv.
+    MissingFile {
+        pid: String,
+        path: PathBuf,
+    }, // A file whose sha1 or size must be computed does not exist.
+    #[cfg(feature = "mysql")]
+    Database(mysql::Error), // Could not query the target database.
+}
+
+impl Error {
+    // Errors in a single row which the rows after it can still be read past,
+    // unlike failing to read the file itself.
+    fn is_row_error(&self) -> bool {
+        match self {
+            Error::Row { source, .. } => !source.is_io_error(),
+            Error::JSONRow { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::CSVError(error) => write!(f, "{}", error),
+            Error::IOError(error) => write!(f, "{}", error),
+            Error::Row { file, line, source } => {
+                let file = file.file_name().unwrap_or_else(|| file.as_os_str());
+                match source.kind() {
+                    // Omit the position csv includes in the message as we already report it.
+                    csv::ErrorKind::Deserialize { err, .. } => {
+                        write!(f, "{}:{}: {}", file.to_string_lossy(), line, err)
+                    }
+                    _ => write!(f, "{}:{}: {}", file.to_string_lossy(), line, source),
+                }
+            }
+            Error::JSONRow { file, line, source } => {
+                let file = file.file_name().unwrap_or_else(|| file.as_os_str());
+                write!(f, "{}:{}: {}", file.to_string_lossy(), line, source)
+            }
+            Error::Config(message) => write!(f, "Invalid configuration: {}", message),
+            Error::InvalidArchive(message) => write!(f, "Invalid zip archive: {}", message),
+            Error::ColumnCount {
+                table,
+                columns,
+                values,
+            } => write!(
+                f,
+                "The rows of table '{}' have {} values but it has {} columns",
+                table, values, columns
+            ),
+            Error::UnknownUser(user) => write!(f, "Unknown user '{}' not found in users.csv", user),
+            Error::UnknownUsers(users) => write!(
+                f,
+                "Unknown users not found in users.csv: {}",
+                users.join(", ")
+            ),
+            Error::InconsistentId {
+                entity,
+                hash,
+                existing,
+                id,
+            } => write!(
+                f,
+                "Inconsistent {} id {} of source ids hash '{}' which already has id {}",
+                entity, id, hash, existing
+            ),
+            Error::OrphanedRevisions(media) => write!(
+                f,
+                "Media revisions of media not found in media.csv: {}",
+                media
+                    .iter()
+                    .map(|(pid, dsid)| format!("'{}' ({})", dsid, pid))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Error::UnknownMedia { pid, dsid } => write!(
+                f,
+                "Unknown media '{}' ({}) not found in media.csv",
+                dsid, pid
+            ),
+            Error::UnknownNode(pid) => write!(f, "Unknown object '{}' not found in nodes.csv", pid),
+            Error::InvalidWeight { pid, weight } => {
+                write!(
+                    f,
+                    "Invalid weight '{}' of '{}' is not an integer",
+                    weight, pid
+                )
+            }
+            Error::InvalidInteger {
+                entity,
+                source_ids,
+                column,
+                value,
+            } => write!(
+                f,
+                "Invalid {} '{}' of {} ({}) is not an integer",
+                column,
+                value,
+                entity,
+                source_ids.join(", ")
+            ),
+            Error::InvalidFlag { pid, column, value } => write!(
+                f,
+                "Invalid {} '{}' of '{}' is neither true nor false",
+                column, value, pid
+            ),
+            Error::InvalidStatus { name, status } => write!(
+                f,
+                "Invalid status '{}' of user '{}' is neither 0 nor 1",
+                status, name
+            ),
+            Error::InvalidDate { pid, date } => {
+                write!(
+                    f,
+                    "Invalid date '{}' of '{}' could not be parsed",
+                    date, pid
+                )
+            }
+            Error::DuplicateSourceId { file, line, ids } => write!(
+                f,
+                "{}:{}: Duplicate source ids ({}) of an earlier row",
+                file,
+                line,
+                ids.join(", ")
+            ),
+            Error::DuplicateMediaRevision { file, ids } => write!(
+                f,
+                "{}: Duplicate source ids ({}) of a media row",
+                file,
+                ids.join(", ")
+            ),
+            Error::MissingFile { pid, path } => write!(
+                f,
+                "Missing file '{}' of '{}' not found on disk",
+                path.display(),
+                pid
+            ),
+            Error::UnknownModel(model) => {
+                write!(f, "Unknown model '{}' has no taxonomy term", model)
+            }
+            Error::UnknownMimeType(mime_type) => {
+                write!(f, "Unknown mime type '{}' has no media bundle", mime_type)
+            }
+            Error::MissingURL { pid, dsid, version } => write!(
+                f,
+                "Remote media ({}, {}, {}) has no url",
+                pid, dsid, version
+            ),
+            Error::UnknownState(state) => {
+                write!(f, "Unknown state '{}' has no moderation state", state)
+            }
+            Error::UnknownFile { pid, dsid, version } => write!(
+                f,
+                "Unknown file '{}' of '{}' ({}) not found in files.csv",
+                version, dsid, pid
+            ),
+            #[cfg(feature = "mysql")]
+            Error::Database(error) => write!(f, "Database error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::CSVError(error) => Some(error),
+            Error::IOError(error) => Some(error),
+            Error::Row { source, .. } => Some(source),
+            Error::JSONRow { source, .. } => Some(source),
+            Error::Config(_) => None,
+            Error::InvalidArchive(_) => None,
+            Error::ColumnCount { .. } => None,
+            Error::UnknownUser(_) => None,
+            Error::UnknownUsers(_) => None,
+            Error::OrphanedRevisions(_) => None,
+            Error::InconsistentId { .. } => None,
+            Error::UnknownMedia { .. } => None,
+            Error::UnknownFile { .. } => None,
+            Error::UnknownNode(_) => None,
+            Error::UnknownModel(_) => None,
+            Error::UnknownMimeType(_) => None,
+            Error::MissingURL { .. } => None,
+            Error::UnknownState(_) => None,
+            Error::InvalidWeight { .. } => None,
+            Error::InvalidFlag { .. } => None,
+            Error::InvalidStatus { .. } => None,
+            Error::InvalidInteger { .. } => None,
+            Error::InvalidDate { .. } => None,
+            Error::DuplicateSourceId { .. } => None,
+            Error::DuplicateMediaRevision { .. } => None,
+            Error::MissingFile { .. } => None,
+            #[cfg(feature = "mysql")]
+            Error::Database(error) => Some(error),
+        }
+    }
+}
+
+impl From<DuplicateSourceId> for Error {
+    fn from(duplicate: DuplicateSourceId) -> Self {
+        Error::DuplicateSourceId {
+            file: duplicate.file,
+            line: duplicate.line,
+            ids: duplicate.source_ids,
+        }
+    }
+}
+
+#[cfg(feature = "mysql")]
+impl From<mysql::Error> for Error {
+    fn from(error: mysql::Error) -> Self {
+        Error::Database(error)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(error: csv::Error) -> Self {
+        Error::CSVError(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::IOError(error)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub(crate) const OUTPUT_BUFFER_SIZE: usize = 1 << 20; // Generated SQL is buffered in 1MiB chunks.
+const PROGRESS_INTERVAL: usize = 1000; // Rows serialized between each progress report.
+
+// Values of `source_row_status` as defined by Drupal's MigrateIdMapInterface.
+pub const MIGRATE_STATUS_IMPORTED: u8 = 0;
+pub const MIGRATE_STATUS_NEEDS_UPDATE: u8 = 1;
+
+const DEFAULT_MEDIA_FILE_FIELD: &str = "field_media_file";
+
+// Replaced in the path alias template by the pid as is, or with the characters
+// which are not safe in a URL replaced by '-', e.g. islandora-1.
+pub(crate) const PATH_ALIAS_PLACEHOLDERS: [&str; 2] = ["{pid}", "{slug}"];
+
+// Options which control the generated SQL, the defaults reproduce the output
+// expected by a standard Islandora 8 site.
+#[derive(Clone, Debug)]
+pub struct MigrationConfig {
+    pub node_type: String,         // Bundle (content type) of the generated nodes.
+    pub langcode: String,          // Language of every generated entity.
+    pub source_row_status: u8, // Status of each row in the migrate_map tables, defaults to imported.
+    pub batch_size: usize,     // Maximum number of rows per INSERT statement.
+    pub deterministic_uuids: bool, // Derive UUIDs from the source ids rather than generating random ones.
+    pub uuid_namespace: Option<String>, // Namespace of the deterministic UUIDs, UUID_NAMESPACE if not given.
+    pub generated_at: Option<u64>, // Timestamp used for generated dates, defaults to the current time.
+    pub unknown_users_as_admin: bool, // Attribute content owned by users missing from users.csv to admin_uid().
+    pub case_insensitive_users: bool, // Match the owners of content to users.csv regardless of case.
+    pub file_names: FileNames,        // Names of the input CSV files.
+    pub delimiter: u8,                // Separates the fields of the CSV files.
+    pub quote: u8,                    // Quotes fields of the CSV files which contain the delimiter.
+    pub role_delimiter: char,         // Separates the roles in the roles column of users.csv.
+    pub input_format: InputFormat,    // Whether the input files are CSV or JSON Lines.
+    pub offsets: Offsets,             // Id of the first entity of each type.
+    pub dialect: Dialect,             // Database the generated SQL is intended for.
+    pub engine: String,               // Storage engine of the migration mapping tables, MySQL only.
+    pub charset: String, // Default character set of the migration mapping tables, MySQL only.
+    pub collation: Option<String>, // Default collation of the migration mapping tables, the charset's default if not given.
+    pub drop_existing_map_tables: bool, // Recreate the migration mapping tables, otherwise existing ones are kept.
+    pub emit_map_tables: bool, // Create and populate the migration mapping tables, otherwise only the entities are written.
+    pub migration_prefix: String, // Start of the id of each migration, e.g. fedora for fedora_nodes.
+    pub map_table_prefix: String, // Start of the name of each migration mapping table, followed by the migration id.
+    pub media_file_fields: IndexMap<String, String>, // Field holding the file of each media bundle, if not field_media_file.
+    pub remote_media_fields: IndexMap<String, String>, // Field holding the url of each remote media bundle, whose media have no file.
+    pub media_bundles: IndexMap<String, String>, // Bundle of media of each mime type, e.g. image/* or *, where media.csv has none.
+    pub model_terms: Option<IndexMap<String, usize>>, // Taxonomy term of each model, field_model is only populated if given.
+    pub thumbnails: Option<ThumbnailMap>, // Thumbnail file of each media, the thumbnail field is only populated if given.
+    pub pid_field: String,                // Field which stores the pid of each node.
+    pub weight_field: String,             // Field which stores the weight of each node.
+    pub published_states: Vec<String>,    // States of the objects whose nodes are published.
+    pub moderation_states: IndexMap<String, String>, // Moderation state of each object state, content moderation is only populated if given.
+    pub moderation_workflow: String,                 // Workflow the moderation states belong to.
+    pub path_alias: Option<String>, // Template of each node's alias, e.g. /islandora/object/{pid}, path aliases are only generated if given.
+    pub revision_log_message: Option<String>, // Template of each media revision's log message, {version} is replaced by the version.
+    pub node_revision_log: Option<String>, // Template of each node revision's log message, {pid} is replaced by the pid.
+    pub passwords: Passwords,              // How the pass column of users.csv is migrated.
+    pub insert_mode: InsertMode,           // Statement the rows are inserted with.
+    pub transactions: Transactions, // Whether the tables are committed separately or all at once.
+    pub disable_foreign_key_checks: bool, // Turn off foreign key checks while loading the rows.
+    pub analyze_tables: bool, // Refresh the index statistics of the tables inserted into once the rows are loaded.
+    pub date_format: Option<String>, // strftime format of dates which are neither timestamps nor ISO 8601.
+    pub compress: bool,              // Gzip the generated SQL files.
+    pub split_output: bool,          // Write each entity to its own file rather than migrate.sql.
+    pub progress: Option<Progress>, // Called periodically as the rows of each entity are serialized.
+    pub limit: Option<usize>, // Only migrate the first rows of each file, intended for testing only.
+    pub latest_version_only: bool, // Only migrate the latest version of each datastream, dropping the media revisions.
+    pub continue_on_error: bool, // Skip rows which can not be parsed, reporting them in Stats, rather than failing.
+    pub uri_scheme: Option<String>, // Stream wrapper of file paths without one, e.g. public://.
+    pub files_directory: Option<PathBuf>, // Location of the files, used when files.csv omits their sha1 or size.
+    pub missing_files: MissingFiles, // Whether files missing from files_directory are an error.
+    pub duplicates: Duplicates, // Whether rows with the same source ids as an earlier row are an error.
+    pub temp_directory: Option<PathBuf>, // Where media.csv and media_revisions.csv are merged, the system's temporary directory if not given.
+    pub reserved_users: IndexMap<String, usize>, // Uid of users which are not in users.csv as Drupal creates them.
+    pub custom_entities: Vec<(String, EntityDump)>, // Written after the built-in entities, e.g. dump_entity::<T>.
+}
+
+impl Default for MigrationConfig {
+    fn default() -> Self {
+        MigrationConfig {
+            node_type: "islandora_object".to_string(),
+            langcode: "en".to_string(),
+            source_row_status: MIGRATE_STATUS_IMPORTED,
+            batch_size: 1000,
+            deterministic_uuids: false,
+            uuid_namespace: None,
+            generated_at: None,
+            unknown_users_as_admin: false,
+            case_insensitive_users: false,
+            file_names: FileNames::default(),
+            delimiter: b',',
+            quote: b'"',
+            role_delimiter: '|',
+            input_format: InputFormat::CSV,
+            offsets: Offsets::default(),
+            dialect: Dialect::MySQL,
+            engine: "InnoDB".to_string(),
+            charset: "utf8mb4".to_string(),
+            collation: None,
+            drop_existing_map_tables: true,
+            emit_map_tables: true,
+            migration_prefix: "fedora".to_string(),
+            map_table_prefix: "migrate_map_".to_string(),
+            media_file_fields: vec![("image".to_string(), "field_media_image".to_string())]
+                .into_iter()
+                .collect(),
+            // Islandora's bundle of videos hosted elsewhere, e.g. YouTube.
+            remote_media_fields: vec![(
+                "remote_video".to_string(),
+                "field_media_oembed_video".to_string(),
+            )]
+            .into_iter()
+            .collect(),
+            // The same bundles as the csv command gives to media, and by type
+            // for the others. Any other type is an error unless "*" is given.
+            media_bundles: vec![
+                ("application/pdf", "document"),
+                ("application/rdf+xml", "file"),
+                ("application/xml", "file"),
+                ("audio/aac", "audio"),
+                ("audio/mpeg", "audio"),
+                ("audio/wav", "audio"),
+                ("image/gif", "image"),
+                ("image/jp2", "file"),
+                ("image/tiff", "file"),
+                ("image/jpeg", "image"),
+                ("image/jpg", "image"),
+                ("image/png", "image"),
+                ("text/plain", "document"),
+                ("text/xml", "file"),
+                ("video/mp4", "video"),
+                ("audio/*", "audio"),
+                ("image/*", "image"),
+                ("video/*", "video"),
+            ]
+            .into_iter()
+            .map(|(mime_type, bundle)| (mime_type.to_string(), bundle.to_string()))
+            .collect(),
+            model_terms: None,
+            thumbnails: None,
+            pid_field: "field_pid".to_string(),
+            weight_field: "field_weight".to_string(),
+            published_states: vec!["Active".to_string()],
+            moderation_states: IndexMap::new(),
+            moderation_workflow: "editorial".to_string(),
+            path_alias: None,
+            revision_log_message: Some("Migrated from Fedora version {version}".to_string()),
+            node_revision_log: Some("Migrated from Fedora object {pid}".to_string()),
+            passwords: Passwords::Omit,
+            insert_mode: InsertMode::Insert,
+            transactions: Transactions::PerTable,
+            disable_foreign_key_checks: false,
+            analyze_tables: false,
+            date_format: None,
+            compress: false,
+            split_output: false,
+            progress: None,
+            limit: None,
+            latest_version_only: false,
+            continue_on_error: false,
+            uri_scheme: None,
+            files_directory: None,
+            missing_files: MissingFiles::Error,
+            duplicates: Duplicates::Error,
+            temp_directory: None,
+            reserved_users: vec![("admin".to_string(), 1)].into_iter().collect(),
+            custom_entities: vec![],
+        }
+    }
+}
+
+// Writes the tables of an entity besides users, files, media and nodes, i.e.
+// crate::dump_entity::<T>, with the ids of the entities written before it.
+pub type EntityDump =
+    fn(&mut dyn Write, &Path, SharedTableIdMaps, &MigrationConfig, &mut Stats) -> Result<()>;
+
+// Callback given the entity, the number of rows serialized so far and the total
+// to serialize, which counts each source row once for each table it is written to.
+// It is shared with the threads which parse the input files so must be Sync.
+type ProgressFn = dyn Fn(&str, usize, usize) + Send + Sync;
+
+#[derive(Clone)]
+pub struct Progress(Arc<ProgressFn>);
+
+impl Progress {
+    pub fn new(callback: impl Fn(&str, usize, usize) + Send + Sync + 'static) -> Self {
+        Progress(Arc::new(callback))
+    }
+
+    pub(crate) fn report(&self, entity: &str, processed: usize, total: usize) {
+        (self.0)(entity, processed, total)
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Progress")
+    }
+}
+
+// When the sha1 or size of a file must be computed but it is not found on disk,
+// either fail or leave them empty as they are in files.csv.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MissingFiles {
+    Error,
+    Skip,
+}
+
+// Rows with the same source ids as an earlier row either fail or are skipped,
+// keeping the earlier row, and listed in Stats::skipped along with the others.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Duplicates {
+    Error,
+    Report,
+}
+
+// Passwords are not migrated by default, leaving the accounts unable to log in
+// until reset. Otherwise they are either plaintext to be hashed as Drupal would,
+// or hashes Drupal already understands which are written as is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Passwords {
+    Omit,
+    Plaintext,
+    Hashed,
+}
+
+// Plain inserts fail if a row with the same key was loaded by an earlier run,
+// so the SQL can instead replace such rows (deleting and re-inserting them), or
+// update them in place with INSERT ... ON DUPLICATE KEY UPDATE, which only MySQL
+// supports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InsertMode {
+    Insert,
+    Replace,
+    Upsert,
+}
+
+// Each table is locked and committed on its own by default. Otherwise all of
+// the rows are loaded in a single transaction so a failure leaves nothing
+// behind, though the migration mapping tables are still created beforehand as
+// creating tables commits implicitly. Split output has one transaction per file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transactions {
+    PerTable,
+    Single,
+}
+
+// Format of the input files, either CSV with a header, or JSON Lines where each
+// line is an object with the same keys as the CSV columns. Objects keep the
+// order of their keys so the rows hash the same as the equivalent CSV.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputFormat {
+    CSV,
+    JSONLines,
+}
+
+// The generated SQL targets MySQL, but can also be written for SQLite so it can
+// be loaded into a throwaway test site. Only the statements surrounding the
+// rows differ, the rows themselves are the same for both.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    MySQL,
+    SQLite,
+}
+
+impl Dialect {
+    pub fn quote(self, value: &str) -> String {
+        match self {
+            Dialect::MySQL => sql_string(value),
+            Dialect::SQLite => sqlite_string(value),
+        }
+    }
+
+    // Like quote() but empty values are NULL.
+    pub(crate) fn nullable(self, value: &str) -> String {
+        if value.is_empty() {
+            "NULL".to_string()
+        } else {
+            self.quote(value)
+        }
+    }
+
+    pub(crate) fn identifier(self, name: &str) -> String {
+        match self {
+            Dialect::MySQL => format!("`{}`", name),
+            Dialect::SQLite => format!("\"{}\"", name),
+        }
+    }
+}
+
+// Names of the CSV files within the input directory, the defaults are the
+// names used by the `csv` command. One of users, files or nodes can instead be
+// read from stdin by naming it STDIN, media and media revisions can not as they
+// are read more than once.
+#[derive(Clone, Debug)]
+pub struct FileNames {
+    pub users: String,
+    pub files: String,
+    pub media: String,
+    pub media_revisions: String,
+    pub nodes: String,
+}
+
+pub const STDIN: &str = "-";
+
+impl FileNames {
+    pub fn reads_stdin(&self) -> bool {
+        [&self.users, &self.files, &self.nodes]
+            .iter()
+            .any(|name| *name == STDIN)
+    }
+}
+
+impl Default for FileNames {
+    fn default() -> Self {
+        FileNames {
+            users: "users.csv".to_string(),
+            files: "files.csv".to_string(),
+            media: "media.csv".to_string(),
+            media_revisions: "media_revisions.csv".to_string(),
+            nodes: "nodes.csv".to_string(),
+        }
+    }
+}
+
+// Id of the first row of each file, each subsequent row is given the next id.
+// The ids are assigned up front and every reference between rows is resolved
+// through them, so they only have to be the same for all the files generated
+// for one site. Rolling back or updating an earlier migration requires the
+// offsets it was generated with, otherwise its destination ids won't match.
+#[derive(Clone, Debug)]
+pub struct Offsets {
+    pub users: usize,
+    pub files: usize,
+    pub media: usize, // Also the revisions, the latest revision's vid is its mid.
+    pub nodes: usize,
+    pub path_aliases: usize, // Also the revisions, each alias has one whose id is its own.
+    pub moderation_states: usize, // Likewise.
+}
+
+impl Default for Offsets {
+    fn default() -> Self {
+        Offsets {
+            users: 2, // Skip anonymous and admin.
+            files: 1, // Drupal ids start at 1, see validate(), so the first of each entity is 1.
+            media: 1,
+            nodes: 1,
+            path_aliases: 1,
+            moderation_states: 1,
+        }
+    }
+}
+
+impl Offsets {
+    // Each node's alias and moderation state are numbered in the same order as
+    // the nodes, from their own offset as they have their own sequences.
+    pub(crate) fn path_alias(&self, nid: usize) -> usize {
+        nid - self.nodes + self.path_aliases
+    }
+
+    pub(crate) fn moderation_state(&self, nid: usize) -> usize {
+        nid - self.nodes + self.moderation_states
+    }
+
+    // The id of a row of the given table inserted for the node with the nid.
+    pub(crate) fn node_table_id(&self, table: &str, nid: usize) -> usize {
+        if table.starts_with("path_alias") {
+            self.path_alias(nid)
+        } else if table.starts_with("content_moderation_state") {
+            self.moderation_state(nid)
+        } else {
+            nid
+        }
+    }
+}
+
+// Offsets which start after the entities already in the target database, so
+// the generated rows do not clash with content created since install. The
+// given offsets are kept where they are larger, including for empty tables.
+// Only the largest id is read, gaps left by deleted content are not reused.
+#[cfg(feature = "mysql")]
+pub fn database_offsets(url: &str, offsets: &Offsets) -> Result<Offsets> {
+    use mysql::prelude::Queryable;
+    let opts = mysql::Opts::from_url(url)
+        .map_err(|error| Error::Config(format!("Invalid database URL: {}", error)))?;
+    let mut conn = mysql::Conn::new(opts)?;
+    // Media, nodes, etc. are written with the vid of their latest revision equal
+    // to their id, so both must start after the largest of either. The tables
+    // of modules which are not installed, e.g. content moderation, are empty.
+    const NO_SUCH_TABLE: u16 = 1146;
+    let mut next = |offset: usize, queries: &[&str]| -> Result<usize> {
+        queries.iter().try_fold(offset, |offset, query| {
+            let max = match conn.query_first::<Option<usize>, _>(*query) {
+                Err(mysql::Error::MySqlError(error)) if error.code == NO_SUCH_TABLE => None,
+                max => max?.flatten(),
+            };
+            Ok(max.map_or(offset, |max| offset.max(max + 1)))
+        })
+    };
+    Ok(Offsets {
+        users: next(offsets.users, &["SELECT MAX(uid) FROM users"])?,
+        files: next(offsets.files, &["SELECT MAX(fid) FROM file_managed"])?,
+        media: next(
+            offsets.media,
+            &[
+                "SELECT MAX(mid) FROM media",
+                "SELECT MAX(vid) FROM media_revision",
+            ],
+        )?,
+        nodes: next(
+            offsets.nodes,
+            &[
+                "SELECT MAX(nid) FROM node",
+                "SELECT MAX(vid) FROM node_revision",
+            ],
+        )?,
+        path_aliases: next(
+            offsets.path_aliases,
+            &[
+                "SELECT MAX(id) FROM path_alias",
+                "SELECT MAX(revision_id) FROM path_alias_revision",
+            ],
+        )?,
+        moderation_states: next(
+            offsets.moderation_states,
+            &[
+                "SELECT MAX(id) FROM content_moderation_state",
+                "SELECT MAX(revision_id) FROM content_moderation_state_revision",
+            ],
+        )?,
+    })
+}
+
+impl MigrationConfig {
+    // Name of the field which references the file of media of the given bundle.
+    pub(crate) fn media_file_field(&self, bundle: &str) -> &str {
+        self.media_file_fields
+            .get(bundle)
+            .map_or(DEFAULT_MEDIA_FILE_FIELD, |field| field.as_str())
+    }
+
+    // The field holding the url of media of the bundle, if it is remote.
+    pub(crate) fn remote_media_field(&self, bundle: &str) -> Option<&str> {
+        self.remote_media_fields
+            .get(bundle)
+            .map(|field| field.as_str())
+    }
+
+    // The bundle of the exact mime type if given, otherwise of its type (e.g.
+    // image/*), otherwise of any mime type (*).
+    pub(crate) fn media_bundle(&self, mime_type: &str) -> Result<&str> {
+        let wildcard = format!("{}/*", mime_type.split('/').next().unwrap_or_default());
+        [mime_type, &wildcard, "*"]
+            .iter()
+            .find_map(|key| self.media_bundles.get(*key))
+            .map(|bundle| bundle.as_str())
+            .ok_or_else(|| Error::UnknownMimeType(mime_type.to_string()))
+    }
+
+    // Name of the migration mapping table of the given migration, e.g. nodes.
+    pub fn map_table(&self, migration: &str) -> String {
+        format!(
+            "{}{}_{}",
+            self.map_table_prefix, self.migration_prefix, migration
+        )
+    }
+
+    pub(crate) fn message_table(&self, migration: &str) -> String {
+        format!("migrate_message_{}_{}", self.migration_prefix, migration)
+    }
+
+    // All of the CSV files have a header and are in the same format.
+    pub(crate) fn csv_reader(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote);
+        builder
+    }
+
+    // Accounts the system creates are not migrated but can still be referred to, e.g. admin.
+    pub(crate) fn reserved_uid(&self, id: &IdMaps, source_ids: &[&str]) -> Option<usize> {
+        match (id, source_ids) {
+            (IdMaps::UID, [user]) if self.case_insensitive_users => self
+                .reserved_users
+                .iter()
+                .find(|(name, _)| name.to_lowercase() == *user)
+                .map(|(_, uid)| *uid),
+            (IdMaps::UID, [user]) => self.reserved_users.get(*user).copied(),
+            _ => None,
+        }
+    }
+
+    // The owner of content whose user is unknown if unknown_users_as_admin, uid
+    // 1 unless reserved_users gives admin another.
+    pub(crate) fn admin_uid(&self) -> usize {
+        self.reserved_uid(&IdMaps::UID, &["admin"]).unwrap_or(1)
+    }
+
+    // Users are looked up by the hash of their name, so if users are case
+    // insensitive both the names in users.csv and the owners of content are
+    // lowercased first. The hash, and the sourceid1 of the users' migration
+    // mapping table, are then of the lowercased name rather than the name as
+    // Drupal's own migration would record it, so rows migrated with and
+    // without the option do not match up.
+    pub(crate) fn normalize_user(&self, user: &mut String) {
+        if self.case_insensitive_users {
+            *user = user.to_lowercase();
+        }
+    }
+
+    // Only valid namespaces are accepted by validate().
+    pub(crate) fn uuid_namespace(&self) -> Uuid {
+        self.uuid_namespace
+            .as_ref()
+            .and_then(|namespace| Uuid::parse_str(namespace).ok())
+            .unwrap_or(UUID_NAMESPACE)
+    }
+
+    // Time of generation, fixed if given so the output is reproducible.
+    pub fn now(&self) -> u64 {
+        self.generated_at.unwrap_or_else(now)
+    }
+
+    // Dates are timestamps when written by the csv command, but other exports
+    // may use ISO 8601 or some other format which must be converted to one.
+    // Missing dates are NULL.
+    pub(crate) fn timestamp(&self, pid: &str, date: &str) -> Result<String> {
+        if date.trim().is_empty() || date.parse::<i64>().is_ok() {
+            return Ok(sql_nullable_int(date));
+        }
+        if let Ok(date) = DateTime::parse_from_rfc3339(date) {
+            return Ok(date.timestamp().to_string());
+        }
+        if let Some(format) = &self.date_format {
+            // Dates without a timezone are assumed to be in UTC.
+            if let Ok(date) = DateTime::parse_from_str(date, format) {
+                return Ok(date.timestamp().to_string());
+            }
+            if let Ok(date) = NaiveDateTime::parse_from_str(date, format) {
+                return Ok(date.and_utc().timestamp().to_string());
+            }
+        }
+        Err(Error::InvalidDate {
+            pid: pid.to_string(),
+            date: date.to_string(),
+        })
+    }
+
+    // Check the options are usable before generating any SQL.
+    pub fn validate(&self) -> Result<()> {
+        // Accept ISO 639 codes as well as the longer variants Drupal allows such
+        // as "zh-hans" or "pt-br".
+        let valid_langcode = (2..=12).contains(&self.langcode.len())
+            && self
+                .langcode
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_langcode {
+            return Err(Error::Config(format!(
+                "'{}' is not a valid language code",
+                self.langcode
+            )));
+        }
+        if self.batch_size == 0 {
+            return Err(Error::Config(
+                "The batch size must be at least 1".to_string(),
+            ));
+        }
+        // Field names are used to build table names so must be valid machine names.
+        let valid_field_name = |field: &str| {
+            !field.is_empty()
+                && field.len() <= 32
+                && field
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        };
+        if let Some(field) = self
+            .media_file_fields
+            .values()
+            .chain(self.remote_media_fields.values())
+            .chain(vec![&self.pid_field, &self.weight_field])
+            .find(|field| !valid_field_name(field))
+        {
+            return Err(Error::Config(format!(
+                "'{}' is not a valid field name",
+                field
+            )));
+        }
+        if !self.moderation_states.is_empty() && !valid_field_name(&self.moderation_workflow) {
+            return Err(Error::Config(format!(
+                "'{}' is not a valid workflow",
+                self.moderation_workflow
+            )));
+        }
+        if let Some(format) = &self.date_format {
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(Error::Config(format!(
+                    "'{}' is not a valid date format",
+                    format
+                )));
+            }
+        }
+        if let Some(template) = &self.path_alias {
+            if !template.starts_with('/')
+                || !PATH_ALIAS_PLACEHOLDERS.iter().any(|p| template.contains(p))
+            {
+                return Err(Error::Config(format!(
+                    "The path alias '{}' must start with / and contain {{pid}} or {{slug}}",
+                    template
+                )));
+            }
+        }
+        if [&self.migration_prefix, &self.map_table_prefix]
+            .iter()
+            .any(|prefix| !valid_field_name(prefix))
+        {
+            return Err(Error::Config(
+                "The migration and mapping table prefixes must be valid machine names".to_string(),
+            ));
+        }
+        // These are written into the preamble as is.
+        if let Some(option) = [
+            Some(&self.engine),
+            Some(&self.charset),
+            self.collation.as_ref(),
+        ]
+        .iter()
+        .flatten()
+        .find(|option| {
+            option.is_empty()
+                || !option
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }) {
+            return Err(Error::Config(format!(
+                "'{}' is not a valid engine, charset or collation",
+                option
+            )));
+        }
+        if let Some(namespace) = &self.uuid_namespace {
+            if Uuid::parse_str(namespace).is_err() {
+                return Err(Error::Config(format!(
+                    "'{}' is not a valid UUID namespace",
+                    namespace
+                )));
+            }
+        }
+        if let Some(scheme) = &self.uri_scheme {
+            let valid_scheme = scheme.len() > 3
+                && scheme.ends_with("://")
+                && scheme[..scheme.len() - 3]
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.');
+            if !valid_scheme {
+                return Err(Error::Config(format!(
+                    "'{}' is not a valid URI scheme, e.g. public://",
+                    scheme
+                )));
+            }
+        }
+        // Inserts where a primary key is 0 are interpreted as not being set and
+        // are incremented to 1, so ids must start from at least 1.
+        let offsets = &self.offsets;
+        if [
+            offsets.users,
+            offsets.files,
+            offsets.media,
+            offsets.nodes,
+            offsets.path_aliases,
+            offsets.moderation_states,
+        ]
+        .contains(&0)
+        {
+            return Err(Error::Config("Offsets must be at least 1".to_string()));
+        }
+        if let Some((user, uid)) = self
+            .reserved_users
+            .iter()
+            .find(|(_, uid)| **uid >= offsets.users)
+        {
+            return Err(Error::Config(format!(
+                "The users offset {} must be greater than the uid {} of '{}'",
+                offsets.users, uid, user
+            )));
+        }
+        if self.delimiter == self.quote || [self.delimiter, self.quote].contains(&b'\n') {
+            return Err(Error::Config(
+                "The delimiter and quote must differ and not be a newline".to_string(),
+            ));
+        }
+        let names = &self.file_names;
+        if names.media == STDIN || names.media_revisions == STDIN {
+            return Err(Error::Config(
+                "Only users, files and nodes can be read from stdin".to_string(),
+            ));
+        }
+        if [&names.users, &names.files, &names.nodes]
+            .iter()
+            .filter(|name| **name == STDIN)
+            .count()
+            > 1
+        {
+            return Err(Error::Config(
+                "Only one file can be read from stdin".to_string(),
+            ));
+        }
+        if self.insert_mode == InsertMode::Upsert && self.dialect == Dialect::SQLite {
+            return Err(Error::Config(
+                "SQLite does not support upserts, replace the rows instead".to_string(),
+            ));
+        }
+        if self.source_row_status > MIGRATE_STATUS_NEEDS_UPDATE {
+            return Err(Error::Config(format!(
+                "{} is not a valid source row status",
+                self.source_row_status
+            )));
+        }
+        Ok(())
+    }
+}
+
+// The (pid, dsid, version) in files.csv of the thumbnail of each media by (pid, dsid).
+pub type ThumbnailMap = IndexMap<(String, String), (String, String, String)>;
+
+pub type Values<'a> = Box<dyn Iterator<Item = Result<String>> + 'a>; // Rows are formatted lazily as they are written.
+
+pub struct Table<'a> {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub values: Values<'a>,
+}
+
+pub fn columns(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+// The number of values in a row formatted as e.g. "(1,'a,b',NULL)", i.e. the
+// commas outside of strings and nested parentheses plus one.
+pub(crate) fn count_values(row: &str, dialect: Dialect) -> usize {
+    let mut count = 1;
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut chars = row.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // SQLite doubles quotes within strings, which toggles quoted twice.
+            '\\' if quoted && dialect == Dialect::MySQL => {
+                chars.next();
+            }
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 1 => count += 1,
+            _ => {}
+        }
+    }
+    count
+}
+
+impl<'a> Table<'a> {
+    // Returns the number of rows written.
+    pub(crate) fn dump(self, writer: &mut impl Write, config: &MigrationConfig) -> Result<usize> {
+        // Nothing is written for empty tables, not even the locking statements.
+        let mut values = self.values.peekable();
+        if values.peek().is_none() {
+            return Ok(0);
+        }
+        let columns = self.columns.join(",");
+        let table = config.dialect.identifier(&self.name);
+        writer.write_all(
+            format!(
+                r#"
+--
+-- Dumping data for table {table}
+--
+
+"#,
+                table = table,
+            )
+            .as_bytes(),
+        )?;
+        // Locking and altering the table would commit the single transaction.
+        let per_table = config.transactions == Transactions::PerTable;
+        if per_table {
+            writer.write_all(
+                match config.dialect {
+                    Dialect::MySQL => format!(
+                        r#"LOCK TABLES {table} WRITE;
+/*!40000 ALTER TABLE {table} DISABLE KEYS */;
+set autocommit=0;
+"#,
+                        table = table,
+                    ),
+                    Dialect::SQLite => "BEGIN;\n".to_string(),
+                }
+                .as_bytes(),
+            )?;
+        }
+        // Split the rows across multiple statements so each stays well under
+        // MySQL's max_allowed_packet.
+        let mut rows = 0;
+        let statement = match config.insert_mode {
+            InsertMode::Replace => "REPLACE",
+            InsertMode::Insert | InsertMode::Upsert => "INSERT",
+        };
+        // The first column is the primary key, or the first part of it, of every
+        // table. Updating the other parts of a composite key sets them to the
+        // values they already have.
+        let update = self
+            .columns
+            .iter()
+            .skip(1)
+            .map(|column| format!("{column}=VALUES({column})", column = column))
+            .collect::<Vec<_>>()
+            .join(",");
+        while values.peek().is_some() {
+            write!(writer, "{} INTO {} ({}) VALUES", statement, table, columns)?;
+            for (i, value) in values.by_ref().take(config.batch_size).enumerate() {
+                let value = value?;
+                // Catches columns which were added or removed without updating
+                // the values, or the other way around, which would otherwise
+                // only be noticed once MySQL rejects the rows or, if the types
+                // happen to match, silently puts values in the wrong columns.
+                if rows == 0 {
+                    let values = count_values(&value, config.dialect);
+                    if values != self.columns.len() {
+                        return Err(Error::ColumnCount {
+                            table: self.name,
+                            columns: self.columns.len(),
+                            values,
+                        });
+                    }
+                }
+                writer.write_all(if i == 0 { b"\n  " } else { b",\n  " })?;
+                writer.write_all(value.as_bytes())?;
+                rows += 1;
+            }
+            if config.insert_mode == InsertMode::Upsert && !update.is_empty() {
+                write!(writer, "\nON DUPLICATE KEY UPDATE {}", update)?;
+            }
+            writer.write_all(b";\n")?;
+        }
+        if per_table {
+            writer.write_all(
+                match config.dialect {
+                    Dialect::MySQL => format!(
+                        r#"/*!40000 ALTER TABLE {table} ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+"#,
+                        table = table,
+                    ),
+                    Dialect::SQLite => "COMMIT;\n".to_string(),
+                }
+                .as_bytes(),
+            )?;
+        }
+        Ok(rows)
+    }
+}
+
+/// The tables of an entity other than those built in, implemented for its rows
+/// outside of this crate as TableSerializer can not be implemented for
+/// MigrateMap of them there. Its rows are looked up in the map, e.g. with
+/// MigrateMap::values() and MigrateMap::lookup(), see crate::dump_entity().
+pub trait EntityTables: SourceRow {
+    fn tables<'a>(map: &'a MigrateMap<Self>, config: &'a MigrationConfig) -> Vec<Table<'a>>;
+}
+
+impl<T> TableSerializer for MigrateMap<T>
+where
+    T: EntityTables,
+{
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        T::tables(self, config)
+    }
+}
+
+pub trait TableSerializer: SourceRows {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>>;
+
+    // Returns the number of rows written to each table.
+    fn dump(&self, mut writer: &mut impl Write, config: &MigrationConfig) -> Result<Stats> {
+        let tables = self.tables(config);
+        let entity = Self::Row::id().entity();
+        let total = self.len() * tables.len();
+        let mut stats = Stats::default();
+        stats.entities.insert(entity.to_string(), self.len());
+        for mut table in tables {
+            let name = table.name.clone();
+            if let Some(progress) = &config.progress {
+                let values = table.values.inspect(move |_| {
+                    if self.processed().is_multiple_of(PROGRESS_INTERVAL) {
+                        progress.report(entity, self.processed(), total);
+                    }
+                });
+                table.values = Box::new(values);
+            }
+            let rows = table.dump(&mut writer, config)?;
+            *stats.tables.entry(name).or_default() += rows;
+            if let Some(progress) = &config.progress {
+                progress.report(entity, self.processed(), total);
+            }
+        }
+        Ok(stats)
+    }
+}
+
+// The input files may be gzip compressed, in which case 'files.csv' can also be
+// found as 'files.csv.gz'.
+pub(crate) fn input_path(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let mut compressed = path.as_os_str().to_owned();
+    compressed.push(".gz");
+    Some(PathBuf::from(compressed)).filter(|path| path.is_file())
+}
+
+// Open the input file, decompressing it as it is read if it is gzip compressed.
+pub(crate) fn open(path: &Path) -> Result<Box<dyn Read>> {
+    let path = input_path(path).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("The file '{}' does not exist", path.display()),
+        )
+    })?;
+    let file = fs::File::open(&path)?;
+    if path.extension().is_some_and(|extension| extension == "gz") {
+        Ok(Box::new(GzDecoder::new(file)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+// Copy the lines after the first skipped ones, ending every line with \n even
+// if the last has no newline or the file has \r\n line endings, so the lines
+// of files copied one after another are never joined.
+pub(crate) fn copy_lines(reader: impl BufRead, writer: &mut impl Write, skip: usize) -> Result<()> {
+    for line in reader.split(b'\n').skip(skip) {
+        let mut line = line?;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        writer.write_all(&line)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// A row as it is read from the input file, with the hash of its source.
+struct Record<T> {
+    line: u64,
+    hash: String,
+    row: T,
+}
+
+type Records<'a, T> = Box<dyn Iterator<Item = Result<Record<T>>> + 'a>;
+
+// Values are given as they would be in the CSV file, where there are only
+// strings, so objects and arrays are not accepted.
+fn json_string(value: serde_json::Value) -> serde_json::Result<String> {
+    match value {
+        serde_json::Value::String(value) => Ok(value),
+        serde_json::Value::Null => Ok(String::new()),
+        serde_json::Value::Bool(value) => Ok(value.to_string()),
+        serde_json::Value::Number(value) => Ok(value.to_string()),
+        _ => Err(serde::de::Error::custom(
+            "objects and arrays are not supported as values",
+        )),
+    }
+}
+
+// The hash of the object's source and the row it deserializes to.
+fn json_line<T>(line: &str) -> serde_json::Result<(String, T)>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let object: IndexMap<String, serde_json::Value> = serde_json::from_str(line)?;
+    let source = object
+        .into_iter()
+        .map(|(key, value)| Ok((key, json_string(value)?)))
+        .collect::<serde_json::Result<Vec<_>>>()?;
+    let pairs = source
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect::<Vec<_>>();
+    let hash = hash(&serialize_map(&pairs));
+    let row = serde_json::from_value(serde_json::Value::Object(
+        source
+            .into_iter()
+            .map(|(key, value)| (key, serde_json::Value::String(value)))
+            .collect(),
+    ))?;
+    Ok((hash, row))
+}
+
+pub trait SourceRow: Sized + serde::de::DeserializeOwned {
+    fn id() -> IdMaps;
+
+    fn offset(offsets: &Offsets) -> usize;
+
+    fn file_name(names: &FileNames) -> &str;
+
+    // The rows of this file out of those parsed up front. Only the built-in
+    // entities are, the rows of custom entities are read by SourceRows::new().
+    fn take(_files: &mut ParsedFiles) -> Rows<Self> {
+        Rows::default()
+    }
+
+    // Columns the CSV file must have, i.e. the fields of the row.
+    fn headers() -> &'static [&'static str];
+
+    // Columns the CSV file may also have.
+    fn optional_headers() -> &'static [&'static str] {
+        &[]
+    }
+
+    fn csv(path: &Path, config: &MigrationConfig) -> Result<Box<dyn Read>> {
+        match Self::file_name(&config.file_names) {
+            STDIN => Ok(Box::new(std::io::stdin())),
+            name => open(&path.join(name)),
+        }
+    }
+
+    fn source_ids(&self) -> Vec<&str>;
+
+    // Fill in the values the CSV file may leave empty once the row is read.
+    fn complete(&mut self, _config: &MigrationConfig) -> Result<()> {
+        Ok(())
+    }
+
+    // Values which are inserted into integer columns as they are, by column,
+    // checked when the row is read rather than failing once the SQL is loaded.
+    // Empty values are allowed, they are inserted as NULL.
+    fn integers(&self) -> Vec<(&'static str, &str)> {
+        vec![]
+    }
+
+    // Likewise for values which may be negative, e.g. the weight of a node.
+    fn signed_integers(&self) -> Vec<(&'static str, &str)> {
+        vec![]
+    }
+
+    // The (pid, dsid) of the datastream and the version, for rows which are one
+    // of the versions of a datastream.
+    fn version(&self) -> Option<([&str; 2], &str)> {
+        None
+    }
+
+    // Rows which are never the latest version of their datastream.
+    fn previous_version() -> bool {
+        false
+    }
+
+    // Rows whose ids are assigned by the table read before them with the same
+    // id map, rather than by their position in this file, e.g. the revisions
+    // of media_revisions.csv which are numbered after those of media.csv.
+    // Rows without an id there are not written.
+    fn ids_assigned_elsewhere() -> bool {
+        false
+    }
+
+    // Rows in other files this row refers to, as the id map to look them up in
+    // and their source ids.
+    fn references(&self, _config: &MigrationConfig) -> Vec<(IdMaps, Vec<&str>)> {
+        vec![]
+    }
+
+    fn source_ids_hash(&self) -> String {
+        hash(&serialize(&self.source_ids()))
+    }
+}
+
+pub trait SourceRows: Sized {
+    type Row: SourceRow;
+    fn new(path: &Path, ids: SharedTableIdMaps, config: &MigrationConfig) -> Result<Self>;
+    // The rows of the file, those skipped as they could not be parsed, and
+    // warnings about those not migrated as they are.
+    fn parse(path: &Path, config: &MigrationConfig) -> Result<ParsedRows<Self::Row>>;
+    fn from_rows(rows: Rows<Self::Row>, ids: SharedTableIdMaps, config: &MigrationConfig) -> Self;
+    fn map(
+        path: &Path,
+        csv: impl Read,
+        config: &MigrationConfig,
+    ) -> Result<(IndexMap<String, Self::Row>, RowHashes)>;
+    fn ids(&self, config: &MigrationConfig) -> TableIdMap;
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn processed(&self) -> usize;
+    fn uid(&self, user: &str, config: &MigrationConfig) -> Result<usize>;
+    fn mid(&self, pid: &str, dsid: &str) -> Result<usize>;
+    fn fid(&self, pid: &str, dsid: &str, version: &str) -> Result<usize>;
+}
+
+#[derive(PartialEq, Eq, Hash, Debug)]
+pub enum IdMaps {
+    FID, // File ID
+    MID, // Media ID
+    NID, // Node ID
+    UID, // User ID
+    VID, // Media Revision ID
+    Custom {
+        entity: &'static str,
+        column: &'static str,
+    }, // ID of an entity added with MigrationConfig::custom_entities.
+}
+
+impl IdMaps {
+    // The id map a table's primary key column is looked up in, field tables
+    // are keyed by the id of the entity given by the table name.
+    pub(crate) fn from_column(table: &str, column: &str) -> Option<IdMaps> {
+        match column {
+            "entity_id" if table.starts_with("media") => Some(IdMaps::MID),
+            "entity_id" if table.starts_with("node") => Some(IdMaps::NID),
+            "entity_id" if table.starts_with("user") => Some(IdMaps::UID),
+            // Each node's moderation state and alias are numbered like the
+            // node, see Offsets::node_table_id().
+            "id" if table.starts_with("content_moderation_state") => Some(IdMaps::NID),
+            "id" if table.starts_with("path_alias") => Some(IdMaps::NID),
+            "fid" => Some(IdMaps::FID),
+            "mid" => Some(IdMaps::MID),
+            "nid" => Some(IdMaps::NID),
+            "uid" => Some(IdMaps::UID),
+            "vid" => Some(IdMaps::VID),
+            _ => None,
+        }
+    }
+
+    // Name of the entity's id, e.g. fid.
+    pub fn column(&self) -> &'static str {
+        match self {
+            IdMaps::FID => "fid",
+            IdMaps::MID => "mid",
+            IdMaps::NID => "nid",
+            IdMaps::UID => "uid",
+            IdMaps::VID => "vid",
+            IdMaps::Custom { column, .. } => column,
+        }
+    }
+
+    pub fn entity(&self) -> &'static str {
+        match self {
+            IdMaps::FID => "file",
+            IdMaps::MID => "media",
+            IdMaps::NID => "node",
+            IdMaps::UID => "user",
+            IdMaps::VID => "media revision",
+            IdMaps::Custom { entity, .. } => entity,
+        }
+    }
+}
+
+pub type TableIdMap = IndexMap<String, usize>; // Map hashes or values to table indices to fetch uid, mid, etc.
+pub type TableIdMaps = IndexMap<IdMaps, TableIdMap>; // Named, table id maps, allow the migration map to look up uid, mid, etc.
+pub type SharedTableIdMaps = Rc<RefCell<TableIdMaps>>;
+
+// Add the ids of a table for the tables after it to look up. Tables which share
+// an id map, i.e. the media revisions, must agree on the ids of the rows they
+// have in common rather than the last one silently replacing the others.
+pub(crate) fn insert_ids(ids: &SharedTableIdMaps, id: IdMaps, table: TableIdMap) -> Result<()> {
+    let entity = id.entity();
+    let mut ids = ids.borrow_mut();
+    let existing = ids.entry(id).or_default();
+    for (hash, id) in table {
+        match existing.get(&hash) {
+            Some(&current) if current != id => {
+                return Err(Error::InconsistentId {
+                    entity,
+                    hash,
+                    existing: current,
+                    id,
+                })
+            }
+            _ => {
+                existing.insert(hash, id);
+            }
+        }
+    }
+    Ok(())
+}
+pub type RowHashes = IndexMap<String, String>; // Map source id hash to the hash of the source row data.
+pub type Rows<T> = (IndexMap<String, T>, RowHashes); // Parsed rows of a file and their hashes.
+pub type ParsedRows<T> = (Rows<T>, Vec<Error>, Vec<Warning>); // Also those skipped and warned about.
+
+pub struct MigrateMap<T>
+where
+    T: SourceRow,
+{
+    pub(crate) map: IndexMap<String, T>, // Map source id hash to source row.
+    pub(crate) hashes: RowHashes, // Detect changes to the source row in subsequent migrations.
+    pub(crate) ids: SharedTableIdMaps, // Look up uid, mid, etc.
+    pub(crate) processed: Cell<usize>, // Rows serialized so far, summed over each of the tables.
+}
+
+impl<T> MigrateMap<T>
+where
+    T: SourceRow,
+{
+    // Take the offset into consideration.
+    pub fn rows(
+        &self,
+        config: &MigrationConfig,
+    ) -> impl std::iter::Iterator<Item = (usize, (&String, &T))> + '_ {
+        let offset = T::offset(&config.offsets);
+        self.map.iter().enumerate().filter_map(move |(index, row)| {
+            let index = if T::ids_assigned_elsewhere() {
+                self.assigned_id(row.0)?
+            } else {
+                offset + index
+            };
+            self.processed.set(self.processed.get() + 1);
+            Some((index, row))
+        })
+    }
+
+    fn assigned_id(&self, hash: &str) -> Option<usize> {
+        self.ids.borrow().get(&T::id())?.get(hash).copied()
+    }
+
+    pub fn values<'a, F>(&'a self, config: &MigrationConfig, map: F) -> Values<'a>
+    where
+        F: Fn((usize, (&String, &T))) -> Result<String> + 'a,
+    {
+        Box::new(self.rows(config).map(map))
+    }
+
+    // The row hash is computed the same way as Drupal's Row::rehash(), i.e.
+    // hash('sha256', serialize($row->getSource())), where the source is every
+    // column of the CSV row keyed by its header in the order they appear in
+    // the file. If the migration's source plugin adds other properties to the
+    // row (constants, configuration, etc) the hashes will not match and the
+    // rows will be considered changed the first time the migration is run.
+    // Rows whose source ids duplicate those of an earlier row are returned
+    // separately, the first of the duplicates is kept. So are rows which can
+    // not be parsed if continue_on_error, rather than failing. Reserved users
+    // already exist in Drupal so are not migrated again, which is warned about.
+    pub(crate) fn read(
+        path: &Path,
+        csv: impl Read,
+        config: &MigrationConfig,
+    ) -> Result<(ParsedRows<T>, Vec<DuplicateSourceId>)> {
+        let records = match config.input_format {
+            InputFormat::CSV => Self::csv_records(path, csv, config)?,
+            InputFormat::JSONLines => Self::json_lines_records(path, csv),
+        };
+        let mut map = IndexMap::new();
+        let mut hashes = RowHashes::new();
+        let mut duplicates = vec![];
+        let mut skipped = vec![];
+        let mut warnings = vec![];
+        for record in records {
+            let Record {
+                line,
+                hash,
+                mut row,
+            } = match record {
+                Err(error) if config.continue_on_error && error.is_row_error() => {
+                    skipped.push(error);
+                    continue;
+                }
+                Err(error) => return Err(error),
+                Ok(record) => record,
+            };
+            row.complete(config)?;
+            let unsigned = row
+                .integers()
+                .into_iter()
+                .filter(|(_, value)| value.parse::<u64>().is_err());
+            let signed = row
+                .signed_integers()
+                .into_iter()
+                .filter(|(_, value)| value.parse::<i64>().is_err());
+            if let Some((column, value)) = unsigned
+                .chain(signed)
+                .find(|(_, value)| !value.trim().is_empty())
+            {
+                let error = Error::InvalidInteger {
+                    entity: T::id().entity(),
+                    source_ids: row.source_ids().iter().map(|s| s.to_string()).collect(),
+                    column,
+                    value: value.to_string(),
+                };
+                if config.continue_on_error {
+                    skipped.push(error);
+                    continue;
+                }
+                return Err(error);
+            }
+            if let Some(uid) = config.reserved_uid(&T::id(), &row.source_ids()) {
+                warnings.push(Warning::new(
+                    &T::id(),
+                    &row.source_ids(),
+                    format!("Reserved user of uid {}, not migrated", uid),
+                ));
+                continue;
+            }
+            let key = row.source_ids_hash();
+            if map.contains_key(&key) {
+                duplicates.push(DuplicateSourceId {
+                    file: path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .to_string(),
+                    line,
+                    source_ids: row.source_ids().iter().map(|s| s.to_string()).collect(),
+                });
+                continue;
+            }
+            hashes.insert(key.clone(), hash);
+            map.insert(key, row);
+        }
+        if config.latest_version_only {
+            latest_versions(&mut map, &mut hashes);
+        }
+        Ok((((map, hashes), skipped, warnings), duplicates))
+    }
+
+    fn csv_records<'a>(
+        path: &'a Path,
+        csv: impl Read + 'a,
+        config: &MigrationConfig,
+    ) -> Result<Records<'a, T>> {
+        let row_error = move |error: csv::Error| Error::Row {
+            file: path.to_path_buf(),
+            line: error.position().map_or(0, |position| position.line()),
+            source: error,
+        };
+        let mut reader = config.csv_reader().from_reader(BufReader::new(csv));
+        let headers = reader.headers().map_err(row_error)?.clone();
+        Ok(Box::new(reader.into_records().map(move |record| {
+            let record = record.map_err(row_error)?;
+            let row: T = record.deserialize(Some(&headers)).map_err(row_error)?;
+            let source = headers.iter().zip(record.iter()).collect::<Vec<_>>();
+            Ok(Record {
+                line: record.position().map_or(0, |position| position.line()),
+                hash: hash(&serialize_map(&source)),
+                row,
+            })
+        })))
+    }
+
+    // Blank lines are ignored, e.g. a trailing one.
+    fn json_lines_records<'a>(path: &'a Path, json: impl Read + 'a) -> Records<'a, T> {
+        let lines = BufReader::new(json).lines().enumerate();
+        Box::new(lines.filter_map(move |(index, line)| {
+            let line_number = index as u64 + 1;
+            let line = match line {
+                Ok(line) if line.trim().is_empty() => return None,
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            Some(
+                json_line(&line)
+                    .map(|(hash, row)| Record {
+                        line: line_number,
+                        hash,
+                        row,
+                    })
+                    .map_err(|source| Error::JSONRow {
+                        file: path.to_path_buf(),
+                        line: line_number,
+                        source,
+                    }),
+            )
+        }))
+    }
+
+    // Rows with the same source ids as an earlier row are an error rather than
+    // silently replacing it, unless they are to be reported with the skipped.
+    fn read_unique(path: &Path, csv: impl Read, config: &MigrationConfig) -> Result<ParsedRows<T>> {
+        let ((rows, mut skipped, warnings), duplicates) = Self::read(path, csv, config)?;
+        let mut duplicates = duplicates.into_iter().map(Error::from);
+        match config.duplicates {
+            Duplicates::Error => match duplicates.next() {
+                Some(duplicate) => Err(duplicate),
+                None => Ok((rows, skipped, warnings)),
+            },
+            Duplicates::Report => {
+                skipped.extend(duplicates);
+                Ok((rows, skipped, warnings))
+            }
+        }
+    }
+
+    // Like uid(), mid(), etc. but does not panic if the row does not exist.
+    pub fn lookup(&self, id: &IdMaps, source_ids: &[&str]) -> Option<usize> {
+        let hash = source_ids_hash(source_ids);
+        self.ids.borrow().get(id)?.get(hash.as_str()).copied()
+    }
+
+    pub fn migrate_map_values<'a>(&'a self, config: &'a MigrationConfig) -> Values<'a> {
+        self.values(config, move |(index, (hash, row))| {
+            let source_ids = row
+                .source_ids()
+                .iter()
+                .map(|id| config.dialect.quote(id))
+                .collect::<Vec<_>>()
+                .join(",");
+            Ok(format!(
+                "({},{},{},{},{},{})",
+                config.dialect.quote(hash),
+                source_ids,
+                index,
+                config.source_row_status,
+                config.now(),
+                config.dialect.quote(&self.hashes[hash])
+            ))
+        })
+    }
+}
+
+impl<T> SourceRows for MigrateMap<T>
+where
+    T: SourceRow,
+{
+    type Row = T;
+    fn new(path: &Path, ids: SharedTableIdMaps, config: &MigrationConfig) -> Result<Self> {
+        Ok(Self::from_rows(Self::parse(path, config)?.0, ids, config))
+    }
+
+    fn parse(path: &Path, config: &MigrationConfig) -> Result<ParsedRows<T>> {
+        let names = &config.file_names;
+        let csv = T::csv(path, config)?;
+        Self::read_unique(&path.join(T::file_name(names)), csv, config)
+    }
+
+    fn from_rows(
+        (mut map, hashes): Rows<T>,
+        ids: SharedTableIdMaps,
+        config: &MigrationConfig,
+    ) -> Self {
+        if let Some(limit) = config.limit {
+            map.drain(limit.min(map.len())..);
+        }
+        Self {
+            map,
+            hashes,
+            ids,
+            processed: Cell::default(),
+        }
+    }
+
+    fn map(
+        path: &Path,
+        csv: impl Read,
+        config: &MigrationConfig,
+    ) -> Result<(IndexMap<String, T>, RowHashes)> {
+        Ok(Self::read_unique(path, csv, config)?.0)
+    }
+
+    fn ids(&self, config: &MigrationConfig) -> TableIdMap {
+        if T::ids_assigned_elsewhere() {
+            return self
+                .map
+                .keys()
+                .filter_map(|hash| Some((hash.clone(), self.assigned_id(hash)?)))
+                .collect();
+        }
+        let offset = Self::Row::offset(&config.offsets);
+        self.map
+            .iter()
+            .enumerate()
+            .map(|(index, (hash, _))| (hash.clone(), offset + index))
+            .collect()
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn processed(&self) -> usize {
+        self.processed.get()
+    }
+
+    fn uid(&self, user: &str, config: &MigrationConfig) -> Result<usize> {
+        let uid = config
+            .reserved_uid(&UserRow::id(), &[user])
+            .or_else(|| self.lookup(&UserRow::id(), &[user]));
+        match uid {
+            Some(uid) => Ok(uid),
+            // Objects may still be owned by accounts which no longer exist.
+            None if config.unknown_users_as_admin => Ok(config.admin_uid()),
+            None => Err(Error::UnknownUser(user.to_string())),
+        }
+    }
+
+    fn mid(&self, pid: &str, dsid: &str) -> Result<usize> {
+        self.lookup(&MediaRow::id(), &[pid, dsid])
+            .ok_or_else(|| Error::UnknownMedia {
+                pid: pid.to_string(),
+                dsid: dsid.to_string(),
+            })
+    }
+
+    fn fid(&self, pid: &str, dsid: &str, version: &str) -> Result<usize> {
+        self.lookup(&FileRow::id(), &[pid, dsid, version])
+            .ok_or_else(|| Error::UnknownFile {
+                pid: pid.to_string(),
+                dsid: dsid.to_string(),
+                version: version.to_string(),
+            })
+    }
+}
+
+// Versions are compared by the number after the last '.', e.g. OBJ.10 is newer
+// than OBJ.9, or as strings if either does not end in a number.
+pub(crate) fn newer_version(version: &str, than: &str) -> bool {
+    let number = |version: &str| version.rsplit('.').next()?.parse::<u64>().ok();
+    match (number(version), number(than)) {
+        (Some(version), Some(than)) => version > than,
+        _ => version > than,
+    }
+}
+
+// Keep only the latest version of each datastream, in the position of the row
+// that is kept.
+fn latest_versions<T>(map: &mut IndexMap<String, T>, hashes: &mut RowHashes)
+where
+    T: SourceRow,
+{
+    if T::previous_version() {
+        map.clear();
+        hashes.clear();
+        return;
+    }
+    let mut latest: IndexMap<[&str; 2], (&str, &str)> = IndexMap::new();
+    for (key, row) in map.iter() {
+        if let Some((datastream, version)) = row.version() {
+            let newer = latest
+                .get(&datastream)
+                .is_none_or(|(_, latest)| newer_version(version, latest));
+            if newer {
+                latest.insert(datastream, (key.as_str(), version));
+            }
+        }
+    }
+    let keep = latest
+        .values()
+        .map(|(key, _)| key.to_string())
+        .collect::<std::collections::HashSet<_>>();
+    map.retain(|key, row| row.version().is_none() || keep.contains(key));
+    hashes.retain(|key, _| map.contains_key(key));
+}
+
+#[derive(Debug)]
+pub struct DuplicateSourceId {
+    pub file: String,            // CSV file of the duplicate row.
+    pub line: u64,               // Line of the duplicate row.
+    pub source_ids: Vec<String>, // Source ids shared with the earlier row.
+}
+
+impl fmt::Display for DuplicateSourceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: Duplicate source ids ({}) of an earlier row",
+            self.file,
+            self.line,
+            self.source_ids.join(", ")
+        )
+    }
+}
+
+// Something done to a row other than migrating it as it is, e.g. giving it a
+// value the CSV file left empty, for review once the SQL is generated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub entity: &'static str,    // Type of the entity of the row, e.g. file.
+    pub source_ids: Vec<String>, // Source ids of the row.
+    pub message: String,         // What was done to the row.
+}
+
+impl Warning {
+    pub(crate) fn new(id: &IdMaps, source_ids: &[&str], message: String) -> Self {
+        Warning {
+            entity: id.entity(),
+            source_ids: source_ids.iter().map(|s| s.to_string()).collect(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}): {}",
+            self.entity,
+            self.source_ids.join(", "),
+            self.message
+        )
+    }
+}
+
+// Number of rows written for each entity and to each table.
+#[derive(Debug, Default)]
+pub struct Stats {
+    pub entities: IndexMap<String, usize>, // Entities created, e.g. user.
+    pub tables: IndexMap<String, usize>,   // Rows inserted into each table.
+    pub skipped: Vec<Error>, // Rows which could not be parsed, if continue_on_error, or duplicates.
+    pub warnings: Vec<Warning>, // Rows which were not migrated as they are.
+}
+
+impl Stats {
+    pub(crate) fn merge(&mut self, other: Stats) {
+        // Media revisions are written by two maps, only the first writes the revisions.
+        for (entity, count) in other.entities {
+            self.entities.entry(entity).or_insert(count);
+        }
+        for (table, rows) in other.tables {
+            *self.tables.entry(table).or_default() += rows;
+        }
+        self.skipped.extend(other.skipped);
+        self.warnings.extend(other.warnings);
+    }
+}
+
+// Parsing a file does not depend on the ids of the others, so they are all
+// parsed in parallel up front and then serialized in order.
+#[derive(Default)]
+pub struct ParsedFiles {
+    pub(crate) users: Rows<UserRow>,
+    pub(crate) files: Rows<FileRow>,
+    pub(crate) media: Rows<MediaRow>,
+    pub(crate) media_revisions: Rows<MediaRevisionRow>,
+    pub(crate) media_revisions_migrate_map: Rows<MediaRevisionMigrateMapRow>,
+    pub(crate) nodes: Rows<NodeRow>,
+    pub(crate) skipped: Vec<Error>, // Rows which could not be parsed, in the order of the files.
+    pub(crate) warnings: Vec<Warning>, // Rows which were dropped when read, e.g. reserved users.
+}
+
+impl ParsedFiles {
+    // Like check_users(), every revision whose media is not in media.csv is
+    // reported at once.
+    pub(crate) fn check_revisions(&self, config: &MigrationConfig) -> Result<()> {
+        let limit = config.limit.unwrap_or(usize::MAX);
+        let mut orphans = IndexMap::new();
+        for row in self.media_revisions_migrate_map.0.values().take(limit) {
+            for (id, reference) in row.references(config) {
+                if let (IdMaps::MID, [pid, dsid]) = (id, reference.as_slice()) {
+                    let found = self
+                        .media
+                        .0
+                        .get_index_of(&source_ids_hash(&[pid, dsid]))
+                        .is_some_and(|index| index < limit);
+                    if !found {
+                        orphans.insert((pid.to_string(), dsid.to_string()), ());
+                    }
+                }
+            }
+        }
+        if orphans.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::OrphanedRevisions(
+                orphans.into_iter().map(|(media, _)| media).collect(),
+            ))
+        }
+    }
+
+    // Rather than failing on the first row which refers to an unknown user
+    // partway through writing the SQL, all of the unknown users are reported up
+    // front so the CSV files can be fixed in one go. If they are owned by admin
+    // instead, each row which refers to one is warned about.
+    pub(crate) fn check_users(&self, config: &MigrationConfig) -> Result<Vec<Warning>> {
+        let limit = config.limit.unwrap_or(usize::MAX);
+        let known = |user: &str| {
+            config.reserved_uid(&IdMaps::UID, &[user]).is_some()
+                || self
+                    .users
+                    .0
+                    .get_index_of(&source_ids_hash(&[user]))
+                    .is_some_and(|index| index < limit)
+        };
+        let mut unknown = IndexMap::new();
+        let mut warnings = vec![];
+        let mut check =
+            |id: IdMaps, source_ids: Vec<&str>, references: Vec<(IdMaps, Vec<&str>)>| {
+                for (reference_id, reference) in references {
+                    if let (IdMaps::UID, [user]) = (reference_id, reference.as_slice()) {
+                        if !known(user) {
+                            unknown.insert(user.to_string(), ());
+                            warnings.push(Warning::new(
+                                &id,
+                                &source_ids,
+                                format!("Unknown user '{}' replaced by admin", user),
+                            ));
+                        }
+                    }
+                }
+            };
+        self.files
+            .0
+            .values()
+            .take(limit)
+            .for_each(|row| check(FileRow::id(), row.source_ids(), row.references(config)));
+        self.media
+            .0
+            .values()
+            .take(limit)
+            .for_each(|row| check(MediaRow::id(), row.source_ids(), row.references(config)));
+        self.media_revisions_migrate_map
+            .0
+            .values()
+            .take(limit)
+            .for_each(|row| {
+                check(
+                    MediaRevisionMigrateMapRow::id(),
+                    row.source_ids(),
+                    row.references(config),
+                )
+            });
+        self.nodes
+            .0
+            .values()
+            .take(limit)
+            .for_each(|row| check(NodeRow::id(), row.source_ids(), row.references(config)));
+        if unknown.is_empty() || config.unknown_users_as_admin {
+            Ok(warnings)
+        } else {
+            Err(Error::UnknownUsers(
+                unknown.into_iter().map(|(user, _)| user).collect(),
+            ))
+        }
+    }
+
+    // Fails if rows refer to users or media which do not exist, otherwise
+    // returns what is done to the rows which are not migrated as they are.
+    pub(crate) fn check(&self, config: &MigrationConfig) -> Result<Vec<Warning>> {
+        let mut warnings = self.warnings.clone();
+        warnings.extend(self.check_users(config)?);
+        self.check_revisions(config)?;
+        let limit = config.limit.unwrap_or(usize::MAX);
+        for file in self.files.0.values().take(limit) {
+            for message in file.coercions(config)? {
+                warnings.push(Warning::new(&FileRow::id(), &file.source_ids(), message));
+            }
+        }
+        for media in self.media.0.values().take(limit) {
+            for message in media.coercions() {
+                warnings.push(Warning::new(&MediaRow::id(), &media.source_ids(), message));
+            }
+        }
+        let revisions = &self.media_revisions_migrate_map.0;
+        for revision in revisions.values().take(limit) {
+            for message in revision.coercions() {
+                warnings.push(Warning::new(
+                    &MediaRevisionMigrateMapRow::id(),
+                    &revision.source_ids(),
+                    message,
+                ));
+            }
+        }
+        Ok(warnings)
+    }
+
+    pub(crate) fn parse(path: &Path, config: &MigrationConfig) -> Result<Self> {
+        let (((users, files), (media, media_revisions_migrate_map)), nodes) = rayon::join(
+            || {
+                rayon::join(
+                    || {
+                        rayon::join(
+                            || MigrateUserMap::parse(path, config),
+                            || MigrateFileMap::parse(path, config),
+                        )
+                    },
+                    || {
+                        rayon::join(
+                            || MigrateMediaMap::parse(path, config),
+                            || MigrateMediaRevisionMapMigrationOnly::parse(path, config),
+                        )
+                    },
+                )
+            },
+            || MigrateNodeMap::parse(path, config),
+        );
+        // Report the error of the first file in order, as parsing sequentially would.
+        let mut skipped = vec![];
+        let mut warnings = vec![];
+        let users = with_skipped(users, &mut skipped, &mut warnings)?;
+        let files = with_skipped(files, &mut skipped, &mut warnings)?;
+        let media = with_skipped(media, &mut skipped, &mut warnings)?;
+        let media_revisions_migrate_map =
+            with_skipped(media_revisions_migrate_map, &mut skipped, &mut warnings)?;
+        let nodes = with_skipped(nodes, &mut skipped, &mut warnings)?;
+        // All the revisions including the latest, i.e. media.csv followed by
+        // media_revisions.csv, without reading either file again.
+        let media_revisions =
+            MediaRevisionRow::merge(&media, &media_revisions_migrate_map, config, &mut skipped)?;
+        Ok(ParsedFiles {
+            users,
+            files,
+            media,
+            media_revisions,
+            media_revisions_migrate_map,
+            nodes,
+            skipped,
+            warnings,
+        })
+    }
+}
+
+fn with_skipped<T>(
+    parsed: Result<ParsedRows<T>>,
+    skipped: &mut Vec<Error>,
+    warnings: &mut Vec<Warning>,
+) -> Result<Rows<T>> {
+    let (rows, errors, dropped) = parsed?;
+    skipped.extend(errors);
+    warnings.extend(dropped);
+    Ok(rows)
+}
@@ -0,0 +1,215 @@
+use crate::common::*;
+use crypto::digest::Digest;
+use crypto::sha1::Sha1;
+use serde::Deserialize;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Deserialize)]
+pub(crate) struct FileRow {
+    pid: String,
+    dsid: String,
+    version: String,
+    created_date: String,
+    mime_type: String,
+    name: String,
+    path: String,
+    user: String,
+    sha1: String,
+    size: String,
+}
+
+impl FileRow {
+    // Bare paths are given the configured scheme, i.e. 'fedora/a.png' becomes
+    // 'public://fedora/a.png'.
+    pub(crate) fn uri(&self, config: &MigrationConfig) -> String {
+        match &config.uri_scheme {
+            Some(scheme) if !self.path.contains("://") => {
+                format!("{}{}", scheme, self.path.trim_start_matches('/'))
+            }
+            _ => self.path.clone(),
+        }
+    }
+
+    pub(crate) fn mime_type(&self) -> &str {
+        mime_type_or_guess(&self.mime_type, &self.name)
+    }
+
+    // Values made up for those the export left empty, to be warned about.
+    pub(crate) fn coercions(&self, config: &MigrationConfig) -> Result<Vec<String>> {
+        let mut coercions = vec![];
+        coercions.extend(mime_type_coercion(&self.mime_type, &self.name));
+        if self.size(config)?.is_empty() {
+            coercions.push("No size, filesize left NULL".to_string());
+        }
+        Ok(coercions)
+    }
+
+    // The file on disk if the files directory is given, which mirrors the
+    // layout of the stream wrappers, i.e. 'public://a.png' is 'a.png' within it.
+    fn local_path(&self, config: &MigrationConfig) -> Result<Option<PathBuf>> {
+        let directory = match &config.files_directory {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+        let relative = self
+            .path
+            .split_once("://")
+            .map_or(self.path.as_str(), |(_, path)| path);
+        let path = directory.join(relative.trim_start_matches('/'));
+        match config.missing_files {
+            _ if path.is_file() => Ok(Some(path)),
+            MissingFiles::Skip => Ok(None),
+            MissingFiles::Error => Err(Error::MissingFile {
+                pid: self.pid.clone(),
+                path,
+            }),
+        }
+    }
+
+    pub(crate) fn sha1(&self, config: &MigrationConfig) -> Result<String> {
+        if !self.sha1.is_empty() {
+            return Ok(self.sha1.clone());
+        }
+        match self.local_path(config)? {
+            Some(path) => {
+                let mut file = fs::File::open(path)?;
+                let mut hasher = Sha1::new();
+                let mut buffer = vec![0; 1 << 16];
+                loop {
+                    let read = file.read(&mut buffer)?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.input(&buffer[..read]);
+                }
+                Ok(hasher.result_str())
+            }
+            None => Ok(String::new()),
+        }
+    }
+
+    pub(crate) fn size(&self, config: &MigrationConfig) -> Result<String> {
+        if !self.size.trim().is_empty() {
+            return Ok(self.size.clone());
+        }
+        match self.local_path(config)? {
+            Some(path) => Ok(path.metadata()?.len().to_string()),
+            None => Ok(String::new()),
+        }
+    }
+}
+
+impl SourceRow for FileRow {
+    fn id() -> IdMaps {
+        IdMaps::FID
+    }
+
+    fn offset(offsets: &Offsets) -> usize {
+        offsets.files
+    }
+
+    fn file_name(names: &FileNames) -> &str {
+        &names.files
+    }
+
+    fn take(files: &mut ParsedFiles) -> Rows<Self> {
+        std::mem::take(&mut files.files)
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &[
+            "pid",
+            "dsid",
+            "version",
+            "created_date",
+            "mime_type",
+            "name",
+            "path",
+            "user",
+            "sha1",
+            "size",
+        ]
+    }
+
+    fn complete(&mut self, config: &MigrationConfig) -> Result<()> {
+        config.normalize_user(&mut self.user);
+        Ok(())
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![self.pid.as_str(), self.dsid.as_str(), self.version.as_str()]
+    }
+
+    fn integers(&self) -> Vec<(&'static str, &str)> {
+        vec![("size", self.size.as_str())]
+    }
+
+    fn references(&self, _config: &MigrationConfig) -> Vec<(IdMaps, Vec<&str>)> {
+        vec![(IdMaps::UID, vec![self.user.as_str()])]
+    }
+
+    fn version(&self) -> Option<([&str; 2], &str)> {
+        Some(([&self.pid, &self.dsid], &self.version))
+    }
+}
+
+pub(crate) type MigrateFileMap = MigrateMap<FileRow>;
+
+impl TableSerializer for MigrateFileMap {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        let mut tables = vec![
+            Table {
+                name: "file_managed".to_string(),
+                columns: columns(&[
+                    "fid", "uuid", "langcode", "uid", "filename", "uri", "filemime", "filesize",
+                    "status", "created", "changed",
+                ]),
+                values: self.values(config, move |(index, (hash, file))| {
+                    Ok(format!(
+                        "({},'{}',{},{},{},{},{},{},1,{},{})",
+                        index,
+                        uuid("file", hash, config),
+                        config.dialect.quote(&config.langcode),
+                        self.uid(&file.user, config)?,
+                        config.dialect.quote(&file.name),
+                        config.dialect.quote(&file.uri(config)),
+                        config.dialect.quote(file.mime_type()),
+                        sql_nullable_int(&file.size(config)?),
+                        config.timestamp(&file.pid, &file.created_date)?,
+                        config.now()
+                    ))
+                }),
+            },
+            Table {
+                name: "filehash".to_string(),
+                columns: columns(&["fid", "sha1"]),
+                values: self.values(config, move |(index, (_, file))| {
+                    Ok(format!(
+                        "({},{})",
+                        index,
+                        config.dialect.quote(&file.sha1(config)?)
+                    ))
+                }),
+            },
+        ];
+        if config.emit_map_tables {
+            tables.push(Table {
+                name: config.map_table("files"),
+                columns: columns(&[
+                    "source_ids_hash",
+                    "sourceid1",
+                    "sourceid2",
+                    "sourceid3",
+                    "destid1",
+                    "source_row_status",
+                    "last_imported",
+                    "hash",
+                ]),
+                values: self.migrate_map_values(config),
+            });
+        }
+        tables
+    }
+}
@@ -0,0 +1,58 @@
+//! The source id hashing of Drupal's migrate module, so the `source_ids_hash`
+//! of the generated mapping tables can be checked against what Drupal computes.
+
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+
+/// Like PHP `serialize()`, but limited to a list of strings as input. As in PHP
+/// the length of each string is in bytes, not characters, and quotes within it
+/// are not escaped, so values must be given as is rather than pre-escaped.
+///
+/// ```
+/// assert_eq!(sql::hashing::serialize(&["pid"]), r#"a:1:{i:0;s:3:"pid";}"#);
+/// ```
+pub fn serialize(values: &[&str]) -> String {
+    let mut result = String::new();
+    result.push_str("a:");
+    result += &values.len().to_string();
+    result.push_str(":{");
+    values.iter().enumerate().for_each(|(i, v)| {
+        result.push_str("i:");
+        result += &i.to_string();
+        result.push(';');
+        result.push_str("s:");
+        result += &v.len().to_string();
+        result.push_str(r#":""#);
+        result += *v;
+        result.push_str(r#"";"#);
+    });
+    result.push('}');
+    result
+}
+
+/// The SHA-256 of the value as lowercase hex, like PHP `hash('sha256', $value)`.
+///
+/// ```
+/// assert_eq!(
+///     sql::hashing::hash("abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+/// );
+/// ```
+pub fn hash(value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.input_str(value);
+    hasher.result_str()
+}
+
+/// The `source_ids_hash` of the row with the given source ids, in the order of
+/// the `sourceid1`, `sourceid2`, etc. columns of its mapping table.
+///
+/// ```
+/// assert_eq!(
+///     sql::hashing::source_ids_hash(&["vcu:38191", "JPG"]),
+///     "000004fd2f49c175d5642673755c3ee43f90b5eebad2694ac52eda44496c611f"
+/// );
+/// ```
+pub fn source_ids_hash(values: &[&str]) -> String {
+    hash(&serialize(values))
+}
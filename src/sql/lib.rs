@@ -15,9 +15,9 @@
 // This takes the files generated by the `csv` command as input as well as the
 // `users.csv` that needs to be generated manually (see the README.md).
 //
-// Also generates and populates the mapping tables, note that the last column
-// used to track changes to the source is not populated by this script, so
-// updates will overwrite the changes made by this script.
+// Also generates and populates the mapping tables, including the hash of each
+// source row so subsequent migrations can detect which rows have changed (see
+// `MigrateMap::map` for how the hash is computed).
 //
 // e.g. Of migration mapping table: migrate_map_fedora_media
 //+------------------------------------------------------------------+-----------+-----------+---------+-------------------+-----------------+---------------+------------------------------------------------------------------+
@@ -26,843 +26,418 @@
 //| 000004fd2f49c175d5642673755c3ee43f90b5eebad2694ac52eda44496c611f | vcu:38191 | JPG       |  304977 |                 0 |               0 |             0 | a2f9248ceef1081dcff2deb8ebecbf680c6a956a790028de6ce1bbd175b8622d |
 //+------------------------------------------------------------------+-----------+-----------+---------+-------------------+-----------------+---------------+------------------------------------------------------------------+
 
+// Each entity's rows and tables are in its own module, with the types and
+// traits they share in common. This module validates the input and drives the
+// generation of the SQL, which MigrationBuilder wraps. The traits are public so
+// other entities can be added to the migration, see dump_entity().
+mod archive;
+mod builder;
+mod common;
+mod files;
+pub mod hashing;
+mod media;
+mod nodes;
+mod users;
+
+pub use archive::{is_archive, InputDirectory};
+pub use builder::{Migration, MigrationBuilder};
+#[cfg(feature = "mysql")]
+pub use common::database_offsets;
+pub use common::{
+    columns, Dialect, DuplicateSourceId, Duplicates, EntityDump, EntityTables, Error, FileNames,
+    IdMaps, InputFormat, InsertMode, MigrateMap, MigrationConfig, MissingFiles, Offsets,
+    ParsedFiles, ParsedRows, Passwords, Progress, Result, RowHashes, Rows, SharedTableIdMaps,
+    SourceRow, SourceRows, Stats, Table, TableIdMap, TableIdMaps, TableSerializer, ThumbnailMap,
+    Transactions, Values, Warning, MIGRATE_STATUS_IMPORTED, MIGRATE_STATUS_NEEDS_UPDATE, STDIN,
+    UUID_NAMESPACE,
+};
+
+use common::*;
 use crypto::digest::Digest;
 use crypto::sha2::Sha256;
 use csv::ReaderBuilder;
-use indexmap::IndexMap; // Use instead of default HashMaps to preserver insertion order used to generate uid, fid, etc.
-use serde::Deserialize;
-use std::cell::RefCell;
+use files::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use indexmap::IndexMap;
+use media::*;
+use nodes::*;
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::fs;
-use std::io::Write;
-use std::io::{BufReader, Seek, SeekFrom};
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
-use std::rc::Rc;
-use std::time::SystemTime;
-use tempfile::tempfile;
-use uuid::Uuid;
-
-// Like PHP serialize(), but limited to a list of strings as input.
-// i.e. serialize(array("pid")); => a:2:{i:0;s:3:"pid";}
-// Used to generate source ids for migrate map tables.
-fn serialize(values: &[&str]) -> String {
-    let mut result = String::new();
-    result.push_str("a:");
-    result += &values.len().to_string();
-    result.push_str(":{");
-    values.iter().enumerate().for_each(|(i, v)| {
-        result.push_str("i:");
-        result += &i.to_string();
-        result.push_str(";");
-        result.push_str("s:");
-        result += &v.len().to_string();
-        result.push_str(r#":""#);
-        result += *v;
-        result.push_str(r#"";"#);
-    });
-    result.push_str("}");
-    result
-}
-
-fn hash(value: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.input_str(value);
-    hasher.result_str()
-}
-
-fn source_ids_hash(values: &[&str]) -> String {
-    hash(&serialize(&values))
-}
-
-fn now() -> u64 {
-    SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
-}
-
-#[derive(Debug)]
-enum Error {
-    CSVError(csv::Error),
-    IOError(std::io::Error),
-}
+use users::*;
 
-impl From<csv::Error> for Error {
-    fn from(error: csv::Error) -> Self {
-        Error::CSVError(error)
+pub fn valid_source_directory(
+    path: &Path,
+    config: &MigrationConfig,
+) -> std::result::Result<(), String> {
+    fn valid_directory(path: &Path) -> std::result::Result<(), String> {
+        if path.is_dir() {
+            Ok(())
+        } else {
+            Err(format!("The directory '{}' does not exist", path.display()))
+        }
     }
+    let input = InputDirectory::open(path, config).map_err(|error| error.to_string())?;
+    let path = input.path();
+    valid_directory(&path)?;
+    let names = &config.file_names;
+    vec![
+        &names.files,
+        &names.media,
+        &names.media_revisions,
+        &names.nodes,
+        &names.users,
+    ]
+    .into_iter()
+    .filter(|file| *file != STDIN)
+    .map(|file| {
+        let path = path.join(file);
+        match input_path(&path) {
+            Some(_) => Ok(()),
+            None => Err(format!("The file '{}' does not exist", path.display())),
+        }
+    })
+    .collect::<std::result::Result<Vec<_>, String>>()?;
+    validate_headers(path, config)
 }
 
-impl From<std::io::Error> for Error {
-    fn from(error: std::io::Error) -> Self {
-        Error::IOError(error)
+// Read only the first line of the file and compare it with the columns the row
+// expects, so changes to the format of the CSV files are caught before any
+// processing begins.
+fn check_headers<T>(
+    path: &Path,
+    config: &MigrationConfig,
+) -> std::result::Result<Option<String>, String>
+where
+    T: SourceRow,
+{
+    let file = T::file_name(&config.file_names);
+    // Reading the header would consume it before the rows are read. JSON Lines
+    // have no header, missing keys are reported as each row is read.
+    if file == STDIN || config.input_format == InputFormat::JSONLines {
+        return Ok(None);
     }
-}
-
-type Result<T> = std::result::Result<T, Error>;
-
-struct Table {
-    name: &'static str,
-    columns: Vec<&'static str>,
-    values: Vec<String>,
-}
-
-impl Table {
-    fn dump(&self, file: &mut fs::File) -> Result<()> {
-        file.write_all(
-            format!(
-                r#"
---
--- Dumping data for table `{table}`
---
-
-LOCK TABLES `{table}` WRITE;
-/*!40000 ALTER TABLE `{table}` DISABLE KEYS */;
-set autocommit=0;
-INSERT INTO `{table}` ({columns}) VALUES 
-  {values};
-/*!40000 ALTER TABLE `{table}` ENABLE KEYS */;
-UNLOCK TABLES;
-commit;
-"#,
-                table = self.name,
-                columns = self.columns.join(","),
-                values = self.values.join(",\n  ")
-            )
-            .as_bytes(),
-        )?;
-        Ok(())
+    let csv =
+        open(&path.join(file)).map_err(|error| format!("Failed to read '{}': {}", file, error))?;
+    let mut reader = config.csv_reader().from_reader(csv);
+    let headers = reader
+        .headers()
+        .map_err(|error| format!("Failed to read the header of '{}': {}", file, error))?;
+    let missing = T::headers()
+        .iter()
+        .filter(|header| !headers.iter().any(|h| h == **header))
+        .copied()
+        .collect::<Vec<_>>();
+    let extra = headers
+        .iter()
+        .filter(|header| !T::headers().contains(header) && !T::optional_headers().contains(header))
+        .collect::<Vec<_>>();
+    let mut problems = vec![];
+    if !missing.is_empty() {
+        problems.push(format!("missing columns: {}", missing.join(", ")));
     }
-}
-
-trait TableSerializer {
-    fn tables(&self) -> Vec<Table>;
-
-    fn dump(&self, mut file: &mut fs::File) -> Result<()> {
-        self.tables()
-            .iter()
-            .map(|table| table.dump(&mut file))
-            .collect()
+    if !extra.is_empty() {
+        problems.push(format!("unexpected columns: {}", extra.join(", ")));
     }
+    Ok(if problems.is_empty() {
+        None
+    } else {
+        Some(format!("'{}' has {}", file, problems.join("; ")))
+    })
 }
 
-trait SourceRow: Sized + serde::de::DeserializeOwned {
-    fn id() -> IdMaps;
-
-    fn offset() -> usize {
-        // inserts where a primary key is 0 are interpreted as not being set and
-        // are incremented to 1, to prevent duplicates we must offset from 1.
-        1
-    }
-
-    fn csv(path: &Path) -> Result<fs::File>;
-
-    fn source_ids(&self) -> Vec<&str>;
-
-    fn source_ids_hash(&self) -> String {
-        hash(&serialize(&self.source_ids()))
+// Check the header of every CSV file, reporting the problems with all of them.
+pub fn validate_headers(path: &Path, config: &MigrationConfig) -> std::result::Result<(), String> {
+    let problems = vec![
+        check_headers::<UserRow>(path, config)?,
+        check_headers::<FileRow>(path, config)?,
+        check_headers::<MediaRow>(path, config)?,
+        check_headers::<MediaRevisionMigrateMapRow>(path, config)?,
+        check_headers::<NodeRow>(path, config)?,
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("\n"))
     }
 }
 
-trait SourceRows: Sized {
-    type Row: SourceRow;
-    fn new(path: &Path, ids: SharedTableIdMaps) -> Result<Self>;
-    fn map(csv: &fs::File) -> Result<IndexMap<String, Self::Row>>;
-    fn ids(&self) -> TableIdMap;
-    fn uid(&self, user: &str) -> usize;
-    fn mid(&self, pid: &str, dsid: &str) -> usize;
-    fn fid(&self, pid: &str, dsid: &str, version: &str) -> usize;
-}
-
-#[derive(PartialEq, Eq, Hash, Debug)]
-enum IdMaps {
-    FID, // File ID
-    MID, // Media ID
-    NID, // Node ID
-    UID, // User ID
-    VID, // Media Revision ID
-}
-
-type TableIdMap = IndexMap<String, usize>; // Map hashes or values to table indices to fetch uid, mid, etc.
-type TableIdMaps = IndexMap<IdMaps, TableIdMap>; // Named, table id maps, allow the migration map to look up uid, mid, etc.
-type SharedTableIdMaps = Rc<RefCell<TableIdMaps>>;
-
-struct MigrateMap<T>
+fn dump<T>(
+    mut writer: &mut dyn Write,
+    files: &mut ParsedFiles,
+    ids: SharedTableIdMaps,
+    config: &MigrationConfig,
+    stats: &mut Stats,
+) -> Result<()>
 where
-    T: SourceRow,
+    T: SourceRows + TableSerializer,
 {
-    map: IndexMap<String, T>, // Map source id hash to source row.
-    ids: SharedTableIdMaps,   // Look up uid, mid, etc.
+    let table_id_map = {
+        let map = T::from_rows(T::Row::take(files), ids.clone(), config);
+        stats.merge(map.dump(&mut writer, config)?);
+        map.ids(config)
+    };
+    insert_ids(&ids, T::Row::id(), table_id_map)
 }
 
-impl<T> MigrateMap<T>
+/// Writes the rows of an entity other than those built in, which is added to
+/// the migration by implementing SourceRow and EntityTables for its rows and
+/// giving `("name".to_string(), dump_entity::<Row>)` in
+/// MigrationConfig::custom_entities.
+///
+/// The ids of an entity must be assigned before anything which refers to them
+/// is written, so an entity has to come after those it depends on. Custom
+/// entities are written after all of the built-in ones (users, files, nodes,
+/// media and media revisions) in the order given, so each can look up the ids
+/// of the built-in entities and of the custom entities before it, e.g. with
+/// MigrateMap::lookup(), but not of those after it, nor can the built-in
+/// entities refer to any of them. Their rows are read from
+/// SourceRow::file_name() in the input directory once it is their turn, and
+/// they are not included in the rollback, the id maps or validate(). Rows which
+/// are skipped, e.g. with MigrationConfig::continue_on_error, are added to
+/// Stats::skipped like those of the built-in entities.
+pub fn dump_entity<T>(
+    mut writer: &mut dyn Write,
+    input: &Path,
+    ids: SharedTableIdMaps,
+    config: &MigrationConfig,
+    stats: &mut Stats,
+) -> Result<()>
 where
-    T: SourceRow,
+    T: EntityTables,
 {
-    // Take the offset into consideration.
-    fn rows(&self) -> impl std::iter::Iterator<Item = (usize, (&String, &T))> + '_ {
-        self.map
-            .iter()
-            .enumerate()
-            .map(|(index, row)| (T::offset() + index, row))
-    }
-
-    fn values<F>(&self, map: F) -> Vec<String>
-    where
-        F: Fn((usize, (&String, &T))) -> String,
-    {
-        self.rows().map(map).collect()
-    }
-
-    fn migrate_map_values(&self) -> Vec<String> {
-        self.values(|(index, (hash, row))| {
-            let source_ids = row
-                .source_ids()
-                .iter()
-                .map(|id| format!("'{}'", id))
-                .collect::<Vec<_>>()
-                .join(",");
-            format!("('{}',{},{})", hash, source_ids, index)
-        })
-    }
+    let table_id_map = {
+        let (rows, skipped, warnings) = MigrateMap::<T>::parse(input, config)?;
+        stats.skipped.extend(skipped);
+        stats.warnings.extend(warnings);
+        let map = MigrateMap::<T>::from_rows(rows, ids.clone(), config);
+        stats.merge(map.dump(&mut writer, config)?);
+        map.ids(config)
+    };
+    insert_ids(&ids, T::id(), table_id_map)
 }
 
-impl<T> SourceRows for MigrateMap<T>
-where
-    T: SourceRow,
-{
-    type Row = T;
-    fn new(path: &Path, ids: SharedTableIdMaps) -> Result<Self> {
-        Ok(Self {
-            map: Self::map(&T::csv(&path)?)?,
-            ids,
-        })
-    }
-
-    fn map(csv: &fs::File) -> Result<IndexMap<String, T>> {
-        let map = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(BufReader::new(csv))
-            .into_deserialize()
-            .collect::<std::result::Result<Vec<T>, csv::Error>>()
-            .map_err(Error::from)?
-            .into_iter()
-            .map(|row| (row.source_ids_hash(), row))
-            .collect();
-        Ok(map)
-    }
-
-    fn ids(&self) -> TableIdMap {
-        self.map
-            .iter()
-            .enumerate()
-            .map(|(index, (hash, _))| (hash.clone(), Self::Row::offset() + index))
-            .collect()
-    }
-
-    fn uid(&self, user: &str) -> usize {
-        // Special case since we do not migrate the admin user as the system creates it.
-        if user == "admin" {
-            1
-        } else {
-            let hash = source_ids_hash(&[user]);
-            let index = UserRow::id();
-            self.ids.borrow()[&index][hash.as_str()]
+type Dump = fn(
+    &mut dyn Write,
+    &mut ParsedFiles,
+    SharedTableIdMaps,
+    &MigrationConfig,
+    &mut Stats,
+) -> Result<()>;
+
+// Entities in the order they are written, as each refers to the ids of those
+// before it, with the name of the file they are written to if split. Nodes come
+// before media as media refer to the node they belong to.
+const ENTITIES: &[(&str, &[Dump])] = &[
+    ("users", &[dump::<MigrateUserMap>]),
+    ("files", &[dump::<MigrateFileMap>]),
+    ("nodes", &[dump::<MigrateNodeMap>]),
+    ("media", &[dump::<MigrateMediaMap>]),
+    (
+        "media_revisions",
+        &[
+            dump::<MigrateMediaRevisionMapExcludingMigration>,
+            dump::<MigrateMediaRevisionMapMigrationOnly>,
+        ],
+    ),
+];
+
+fn write_tables(path: &Path, writer: &mut impl Write, config: &MigrationConfig) -> Result<Stats> {
+    let mut files = ParsedFiles::parse(path, config)?;
+    let warnings = files.check(config)?;
+    let ids = SharedTableIdMaps::new(RefCell::new(TableIdMaps::new()));
+    let mut stats = Stats {
+        skipped: std::mem::take(&mut files.skipped),
+        warnings,
+        ..Stats::default()
+    };
+    for (_, dumps) in ENTITIES {
+        for dump in dumps.iter() {
+            dump(writer, &mut files, ids.clone(), config, &mut stats)?;
         }
     }
-
-    fn mid(&self, pid: &str, dsid: &str) -> usize {
-        let hash = source_ids_hash(&[pid, dsid]);
-        let index = MediaRow::id();
-        self.ids.borrow()[&index][hash.as_str()]
-    }
-
-    fn fid(&self, pid: &str, dsid: &str, version: &str) -> usize {
-        let hash = source_ids_hash(&[pid, dsid, version]);
-        let index = FileRow::id();
-        self.ids.borrow()[&index][hash.as_str()]
-    }
-}
-
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct UserRow {
-    name: String,
-    pass: String,
-    mail: String,
-    status: String,
-    timezone: String,
-    language: String,
-}
-
-impl SourceRow for UserRow {
-    fn id() -> IdMaps {
-        IdMaps::UID
-    }
-
-    fn offset() -> usize {
-        2
-    }
-
-    fn csv(path: &Path) -> Result<fs::File> {
-        Ok(fs::File::open(path.join("users.csv"))?)
-    }
-
-    fn source_ids(&self) -> Vec<&str> {
-        vec![self.name.as_str()]
+    for (_, dump) in &config.custom_entities {
+        dump(writer, path, ids.clone(), config, &mut stats)?;
     }
+    Ok(stats)
 }
 
-type MigrateUserMap = MigrateMap<UserRow>;
-
-impl TableSerializer for MigrateUserMap {
-    fn tables(&self) -> Vec<Table> {
-        vec![
-            Table {
-                name: "users",
-                columns: vec!["uid", "uuid", "langcode"],
-                values: self.values(|(index, _)| {
-                    let uuid = Uuid::new_v4();
-                    format!("({},'{}','en')", index, uuid)
-                }),
-            },
-            Table {
-                name: "users_field_data",
-                columns: vec![
-                    "uid",
-                    "langcode",
-                    "name",
-                    "created",
-                    "access",
-                    "default_langcode",
-                ],
-                values: self.values(|(index, (_, user))| {
-                    format!("({},'en','{}',{},0,1)", index, user.name, now())
-                }),
-            },
-            Table {
-                name: "migrate_map_fedora_users",
-                columns: vec!["source_ids_hash", "sourceid1", "destid1"],
-                values: self.migrate_map_values(),
-            },
-        ]
-    }
-}
-
-#[derive(Deserialize)]
-struct FileRow {
-    pid: String,
-    dsid: String,
-    version: String,
-    created_date: String,
-    mime_type: String,
-    name: String,
-    path: String,
-    user: String,
-    sha1: String,
-    size: String,
-}
-
-impl SourceRow for FileRow {
-    fn id() -> IdMaps {
-        IdMaps::FID
-    }
-
-    fn csv(path: &Path) -> Result<fs::File> {
-        Ok(fs::File::open(path.join("files.csv"))?)
-    }
-
-    fn source_ids(&self) -> Vec<&str> {
-        vec![self.pid.as_str(), self.dsid.as_str(), self.version.as_str()]
-    }
-}
-
-type MigrateFileMap = MigrateMap<FileRow>;
-
-impl TableSerializer for MigrateFileMap {
-    fn tables(&self) -> Vec<Table> {
-        vec![
-            Table {
-                name: "file_managed",
-                columns: vec![
-                    "fid", "uuid", "langcode", "uid", "filename", "uri", "filemime", "filesize",
-                    "status", "created", "changed",
-                ],
-                values: self.values(|(index, (_, file))| {
-                    format!(
-                        "({},'{}','en',{},'{}','{}','{}',{},1,{},{})",
-                        index,
-                        Uuid::new_v4(),
-                        self.uid(&file.user),
-                        &file.name,
-                        &file.path,
-                        &file.mime_type,
-                        &file.size,
-                        &file.created_date,
-                        now()
-                    )
-                }),
-            },
-            Table {
-                name: "filehash",
-                columns: vec!["fid", "sha1"],
-                values: self.values(|(index, (_, file))| format!("({},'{}')", index, &file.sha1)),
-            },
-            Table {
-                name: "migrate_map_fedora_files",
-                columns: vec![
-                    "source_ids_hash",
-                    "sourceid1",
-                    "sourceid2",
-                    "sourceid3",
-                    "destid1",
-                ],
-                values: self.migrate_map_values(),
-            },
-        ]
+// Like write_tables() but each entity is written to its own file so they can
+// be loaded separately, preceded by the file which creates the tables. The
+// names of the files are returned in the order they are to be loaded.
+fn write_split_tables(
+    path: &Path,
+    dest: &Path,
+    config: &MigrationConfig,
+) -> Result<(Stats, Vec<String>)> {
+    let mut outputs = vec![];
+    if config.emit_map_tables {
+        write_output(dest, "00_preamble.sql", config, |writer| {
+            Ok(writer.write_all(create_tables_preamble(config).as_bytes())?)
+        })?;
+        outputs.push(output_name("00_preamble.sql", config));
     }
-}
-
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct MediaRow {
-    pid: String,
-    dsid: String,
-    version: String,
-    bundle: String,
-    created_date: String,
-    file_size: String,
-    label: String,
-    mime_type: String,
-    name: String,
-    user: String,
-}
-
-impl SourceRow for MediaRow {
-    fn id() -> IdMaps {
-        IdMaps::MID
+    let mut files = ParsedFiles::parse(path, config)?;
+    let warnings = files.check(config)?;
+    let ids = SharedTableIdMaps::new(RefCell::new(TableIdMaps::new()));
+    let mut stats = Stats {
+        skipped: std::mem::take(&mut files.skipped),
+        warnings,
+        ..Stats::default()
+    };
+    for (i, (name, dumps)) in ENTITIES.iter().enumerate() {
+        let file = format!("{:02}_{}.sql", i + 1, name);
+        write_output(dest, &file, config, |writer| {
+            writer.write_all(transaction_start(config).as_bytes())?;
+            dumps
+                .iter()
+                .try_for_each(|dump| dump(writer, &mut files, ids.clone(), config, &mut stats))?;
+            Ok(writer.write_all(transaction_end(config).as_bytes())?)
+        })?;
+        outputs.push(output_name(&file, config));
     }
-
-    fn csv(path: &Path) -> Result<fs::File> {
-        Ok(fs::File::open(path.join("media.csv"))?)
+    for (i, (name, dump)) in config.custom_entities.iter().enumerate() {
+        let file = format!("{:02}_{}.sql", ENTITIES.len() + i + 1, name);
+        write_output(dest, &file, config, |writer| {
+            writer.write_all(transaction_start(config).as_bytes())?;
+            dump(writer, path, ids.clone(), config, &mut stats)?;
+            Ok(writer.write_all(transaction_end(config).as_bytes())?)
+        })?;
+        outputs.push(output_name(&file, config));
     }
-
-    fn source_ids(&self) -> Vec<&str> {
-        vec![self.pid.as_str(), self.dsid.as_str()]
+    if config.analyze_tables {
+        let file = format!(
+            "{:02}_analyze.sql",
+            ENTITIES.len() + config.custom_entities.len() + 1
+        );
+        write_output(dest, &file, config, |writer| {
+            Ok(writer.write_all(analyze_tables(&stats, config).as_bytes())?)
+        })?;
+        outputs.push(output_name(&file, config));
     }
+    Ok((stats, outputs))
 }
 
-type MigrateMediaMap = MigrateMap<MediaRow>;
-
-impl TableSerializer for MigrateMediaMap {
-    fn tables(&self) -> Vec<Table> {
-        vec![
-            Table {
-                name: "media",
-                columns: vec!["mid", "vid", "bundle", "uuid", "langcode"],
-                values: self.values(|(index, (_, media))| {
-                    format!(
-                        "({},{},'{}','{}','en')",
-                        index,
-                        index,
-                        &media.bundle,
-                        Uuid::new_v4()
-                    )
-                }),
-            },
-            Table {
-                name: "media_field_data",
-                columns: vec![
-                    "mid",
-                    "vid",
-                    "bundle",
-                    "langcode",
-                    "status",
-                    "uid",
-                    "name",
-                    "created",
-                    "changed",
-                    "default_langcode",
-                ],
-                values: self.values(|(index, (_, media))| {
-                    format!(
-                        "({},{},'{}','en',1,{},'{}',{},{}, 1)",
-                        index,
-                        index,
-                        &media.bundle,
-                        self.uid(&media.user),
-                        &media.name,
-                        &media.created_date,
-                        &media.created_date,
-                    )
-                }),
-            },
-            Table {
-                name: "migrate_map_fedora_media",
-                columns: vec!["source_ids_hash", "sourceid1", "sourceid2", "destid1"],
-                values: self.migrate_map_values(),
-            },
-        ]
-    }
-}
-
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct MediaRevisionRow {
-    pid: String,
-    dsid: String,
-    version: String,
-    bundle: String,
-    created_date: String,
-    file_size: String,
-    label: String,
-    mime_type: String,
-    name: String,
-    user: String,
-}
-
-impl SourceRow for MediaRevisionRow {
-    fn id() -> IdMaps {
-        IdMaps::VID
-    }
-
-    fn csv(path: &Path) -> Result<fs::File> {
-        // Media rows are also part of media_revisions so we merge the two files
-        // with the media.csv being first to preserve the correct order for mid
-        // and vid. Additionally we need to remove the additional header in
-        // media_revisions.csv.
-        let mut csv = tempfile()?;
-        csv.write_all(&fs::read(path.join("media.csv"))?)?;
-        let media_revisions = fs::read_to_string(path.join("media_revisions.csv"))?
-            .lines()
-            .skip(1)
-            .collect::<Vec<&str>>()
-            .join("\n");
-        csv.write_all(&media_revisions.as_bytes())?;
-        csv.seek(SeekFrom::Start(0)).unwrap();
-        Ok(csv)
-    }
-
-    fn source_ids(&self) -> Vec<&str> {
-        vec![self.pid.as_str(), self.dsid.as_str(), self.version.as_str()]
-    }
-}
-
-type MigrateMediaRevisionMapExcludingMigration = MigrateMap<MediaRevisionRow>;
-
-impl TableSerializer for MigrateMediaRevisionMapExcludingMigration {
-    fn tables(&self) -> Vec<Table> {
-        vec![
-            Table {
-                name: "media_revision",
-                columns: vec![
-                    "mid",
-                    "vid",
-                    "langcode",
-                    "revision_user",
-                    "revision_created",
-                    "revision_default",
-                ],
-                values: self.values(|(index, (_, media_revision))| {
-                    format!(
-                        "({},{},'en',{},{},1)",
-                        index,
-                        index,
-                        self.uid(&media_revision.user),
-                        &media_revision.created_date
-                    )
-                }),
-            },
-            Table {
-                name: "media_field_revision",
-                columns: vec![
-                    "mid",
-                    "vid",
-                    "langcode",
-                    "status",
-                    "uid",
-                    "name",
-                    "created",
-                    "changed",
-                    "default_langcode",
-                ],
-                values: self.values(|(index, (_, media))| {
-                    format!(
-                        "({},{},'en',1,{},'{}',{},{}, 1)",
-                        self.mid(&media.pid, &media.dsid),
-                        index,
-                        self.uid(&media.user),
-                        &media.name,
-                        &media.created_date,
-                        &media.created_date,
-                    )
-                }),
-            },
-            Table {
-                name: "file_usage",
-                columns: vec![
-                    "fid",
-                    "module",
-                    "type",
-                    "id",
-                    "count",
-                ],
-                values: self.values(|(index, (_, media))| {
-                    format!(
-                        "({},'file','media',{},1)",
-                        self.fid(&media.pid, &media.dsid, &media.version),
-                        index,
-                    )
-                }),
-            },
-        ]
+// The ids inserted into each table are contiguous so the rows can be deleted
+// by range, except for the migration mapping tables which are deleted by hash.
+// The tables are returned in the reverse of the order they are inserted.
+fn delete_statements<T>(map: &MigrateMap<T>, config: &MigrationConfig) -> Vec<String>
+where
+    T: SourceRow,
+    MigrateMap<T>: TableSerializer,
+{
+    let ids = map.ids.borrow();
+    let hashes = map.map.keys().collect::<Vec<_>>();
+    let mut statements = vec![];
+    for table in map.tables(config).into_iter().rev() {
+        let name = config.dialect.identifier(&table.name);
+        if table.name.starts_with(&config.map_table_prefix) {
+            for hashes in hashes.chunks(config.batch_size) {
+                let hashes = hashes
+                    .iter()
+                    .map(|hash| config.dialect.quote(hash))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                statements.push(format!(
+                    "DELETE FROM {} WHERE source_ids_hash IN ({});",
+                    name, hashes
+                ));
+            }
+        } else if let Some(range) = IdMaps::from_column(&table.name, &table.columns[0])
+            .and_then(|id| ids.get(&id))
+            .filter(|range| !range.is_empty())
+        {
+            let id = |id: &usize| config.offsets.node_table_id(&table.name, *id);
+            let min = range.values().map(id).min().unwrap();
+            let max = range.values().map(id).max().unwrap();
+            statements.push(format!(
+                "DELETE FROM {} WHERE {} BETWEEN {} AND {};",
+                name, table.columns[0], min, max
+            ));
+        }
     }
+    statements
 }
 
-// Need a separate structure for revision mapping since the migration is meant
-// to only handle those revisions which are not the latest.
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct MediaRevisionMigrateMapRow {
-    pid: String,
-    dsid: String,
-    version: String,
-    bundle: String,
-    created_date: String,
-    file_size: String,
-    label: String,
-    mime_type: String,
-    name: String,
-    user: String,
+// Read the rows and add their ids for the rows read after them to look up.
+fn load<T>(path: &Path, ids: SharedTableIdMaps, config: &MigrationConfig) -> Result<MigrateMap<T>>
+where
+    T: SourceRow,
+{
+    let map = MigrateMap::<T>::new(path, ids.clone(), config)?;
+    insert_ids(&ids, T::id(), map.ids(config))?;
+    Ok(map)
 }
 
-impl SourceRow for MediaRevisionMigrateMapRow {
-    fn id() -> IdMaps {
-        IdMaps::VID
-    }
-
-    fn csv(path: &Path) -> Result<fs::File> {
-        Ok(fs::File::open(path.join("media_revisions.csv"))?)
-    }
-
-    fn source_ids(&self) -> Vec<&str> {
-        vec![self.pid.as_str(), self.dsid.as_str(), self.version.as_str()]
-    }
+fn rollback<T>(path: &Path, ids: SharedTableIdMaps, config: &MigrationConfig) -> Result<Vec<String>>
+where
+    T: SourceRow,
+    MigrateMap<T>: TableSerializer,
+{
+    Ok(delete_statements(&load::<T>(path, ids, config)?, config))
 }
 
-type MigrateMediaRevisionMapMigrationOnly = MigrateMap<MediaRevisionMigrateMapRow>;
-
-impl TableSerializer for MigrateMediaRevisionMapMigrationOnly {
-    fn tables(&self) -> Vec<Table> {
-        vec![Table {
-            name: "migrate_map_fedora_media_revisions",
-            columns: vec![
-                "source_ids_hash",
-                "sourceid1",
-                "sourceid2",
-                "sourceid3",
-                "destid1",
-            ],
-            values: self.migrate_map_values(),
-        }]
+// Builds the id maps the same way as write_tables() and deletes in reverse
+// dependency order, i.e. nodes before the media which references them, etc.
+fn write_rollback(path: &Path, writer: &mut impl Write, config: &MigrationConfig) -> Result<()> {
+    let ids = SharedTableIdMaps::new(RefCell::new(TableIdMaps::new()));
+    let statements = vec![
+        rollback::<UserRow>(path, ids.clone(), config)?,
+        rollback::<FileRow>(path, ids.clone(), config)?,
+        rollback::<NodeRow>(path, ids.clone(), config)?,
+        rollback::<MediaRow>(path, ids.clone(), config)?,
+        rollback::<MediaRevisionRow>(path, ids.clone(), config)?,
+        rollback::<MediaRevisionMigrateMapRow>(path, ids, config)?,
+    ];
+    for statement in statements.into_iter().rev().flatten() {
+        writeln!(writer, "{}", statement)?;
     }
+    Ok(())
 }
 
-#[derive(Deserialize)]
-#[allow(dead_code)]
-struct NodeRow {
-    pid: String,
-    created_date: String,
-    label: String,
-    weight: String,
-    model: String,
-    modified_date: String,
-    state: String,
-    user: String,
-    display_hint: String,
-    parents: String,
-}
-
-impl SourceRow for NodeRow {
-    fn id() -> IdMaps {
-        IdMaps::NID
-    }
-
-    fn offset() -> usize {
-        100 // Account for default content created on install.
-    }
-
-    fn csv(path: &Path) -> Result<fs::File> {
-        Ok(fs::File::open(path.join("nodes.csv"))?)
-    }
-
-    fn source_ids(&self) -> Vec<&str> {
-        vec![self.pid.as_str()]
-    }
+// Migration mapping tables do not exist until a migration is run so we must
+// create them here since this is intended to run before any content is created.
+struct MigrateMapTable {
+    name: &'static str, // Migration id without the prefix.
+    sources: usize,
 }
 
-type MigrateNodeMap = MigrateMap<NodeRow>;
-
-impl TableSerializer for MigrateNodeMap {
-    fn tables(&self) -> Vec<Table> {
-        vec![
-            Table {
-                name: "node",
-                columns: vec!["nid", "vid", "type", "uuid", "langcode"],
-                values: self.values(|(index, _)| {
-                    format!(
-                        "({},{},'islandora_object','{}','en')",
-                        index,
-                        index,
-                        Uuid::new_v4()
-                    )
-                }),
-            },
-            Table {
-                name: "node_revision",
-                columns: vec![
-                    "nid",
-                    "vid",
-                    "langcode",
-                    "revision_uid",
-                    "revision_timestamp",
-                    "revision_default",
-                ],
-                values: self.values(|(index, (_, node))| {
-                    format!(
-                        "({},{},'en',{},{},1)",
-                        index,
-                        index,
-                        self.uid(&node.user),
-                        &node.modified_date
-                    )
-                }),
-            },
-            Table {
-                name: "node_field_data",
-                columns: vec![
-                    "nid",
-                    "vid",
-                    "type",
-                    "langcode",
-                    "status",
-                    "uid",
-                    "title",
-                    "created",
-                    "changed",
-                    "promote",
-                    "sticky",
-                    "default_langcode",
-                ],
-                values: self.values(|(index, (_, node))| {
-                    format!(
-                        "({},{},'islandora_object','en',1,{},'{}',{},{},1,0,1)",
-                        index,
-                        index,
-                        self.uid(&node.user),
-                        &node.label.escape_default(),
-                        &node.created_date,
-                        &node.modified_date,
-                    )
-                }),
-            },
-            Table {
-                name: "node_field_revision",
-                columns: vec![
-                    "nid",
-                    "vid",
-                    "langcode",
-                    "status",
-                    "uid",
-                    "title",
-                    "created",
-                    "changed",
-                    "promote",
-                    "sticky",
-                    "default_langcode",
-                ],
-                values: self.values(|(index, (_, node))| {
-                    format!(
-                        "({},{},'en',1,{},'{}',{},{},1,0,1)",
-                        index,
-                        index,
-                        self.uid(&node.user),
-                        &node.label.escape_default(),
-                        &node.created_date,
-                        &node.modified_date,
-                    )
-                }),
-            },
-            Table {
-                name: "migrate_map_fedora_nodes",
-                columns: vec!["source_ids_hash", "sourceid1", "destid1"],
-                values: self.migrate_map_values(),
-            },
-        ]
+impl MigrateMapTable {
+    fn create(&self, config: &MigrationConfig) -> String {
+        match config.dialect {
+            Dialect::MySQL => self.create_mysql(config),
+            Dialect::SQLite => self.create_sqlite(config),
+        }
     }
-}
 
-pub fn valid_source_directory(path: &Path) -> std::result::Result<(), String> {
-    fn valid_directory(path: &Path) -> std::result::Result<(), String> {
-        if path.is_dir() {
-            Ok(())
+    // Tables created by a migration which has already run hold its state, so
+    // unless they are dropped they are only created if they do not exist.
+    fn drop(table: &str, config: &MigrationConfig) -> String {
+        if config.drop_existing_map_tables {
+            format!(
+                "DROP TABLE IF EXISTS {};\n",
+                config.dialect.identifier(table)
+            )
         } else {
-            Err(format!("The directory '{}' does not exist", path.display()))
+            String::new()
         }
     }
-    valid_directory(&path)?;
-    vec![
-        "files.csv",
-        "media.csv",
-        "media_revisions.csv",
-        "nodes.csv",
-        "users.csv",
-    ]
-    .into_iter()
-    .map(|file| {
-        let path = path.join(file);
-        if path.is_file() && path.exists() {
-            Ok(())
+
+    fn if_not_exists(config: &MigrationConfig) -> &'static str {
+        if config.drop_existing_map_tables {
+            ""
         } else {
-            Err(format!("The file '{}' does not exist", path.display()))
+            "IF NOT EXISTS "
         }
-    })
-    .collect::<std::result::Result<Vec<_>, String>>()?;
-    Ok(())
-}
-
-fn dump<T>(mut file: &mut fs::File, path: &Path, ids: SharedTableIdMaps) -> Result<()>
-where
-    T: SourceRows + TableSerializer,
-{
-    let table_id_map = {
-        let map = T::new(&path, ids.clone())?;
-        map.dump(&mut file)?;
-        map.ids()
-    };
-    ids.borrow_mut().insert(T::Row::id(), table_id_map);
-    Ok(())
-}
-
-fn write_tables(path: &Path, mut file: fs::File) -> Result<()> {
-    let ids = SharedTableIdMaps::new(RefCell::new(TableIdMaps::new()));
-    dump::<MigrateUserMap>(&mut file, &path, ids.clone())?;
-    dump::<MigrateFileMap>(&mut file, &path, ids.clone())?;
-    dump::<MigrateMediaMap>(&mut file, &path, ids.clone())?;
-    dump::<MigrateMediaRevisionMapExcludingMigration>(&mut file, &path, ids.clone())?;
-    dump::<MigrateMediaRevisionMapMigrationOnly>(&mut file, &path, ids.clone())?;
-    dump::<MigrateNodeMap>(&mut file, &path, ids)?;
-    Ok(())
-}
-
-// Migration mapping tables do not exist until a migration is run so we must
-// create them here since this is intended to run before any content is created.
-struct MigrateMapTable {
-    name: &'static str,
-    sources: usize,
-}
+    }
 
-impl ToString for MigrateMapTable {
-    fn to_string(&self) -> String {
+    fn create_mysql(&self, config: &MigrationConfig) -> String {
         let sources = (1..self.sources + 1)
             .map(|i| format!("`sourceid{}` varchar(255) NOT NULL,", i))
             .collect::<Vec<_>>()
@@ -872,16 +447,24 @@ impl ToString for MigrateMapTable {
             .collect::<Vec<_>>()
             .join(",");
         let source_keys = format!("KEY `source` ({})", source_keys);
+        let options = format!(
+            "ENGINE={} DEFAULT CHARSET={}{}",
+            config.engine,
+            config.charset,
+            config
+                .collation
+                .as_ref()
+                .map_or(String::new(), |collation| format!(" COLLATE={}", collation))
+        );
         return format!(
             r#"
 --
--- Table structure for table `migrate_map_{name}`
+-- Table structure for table `{map}`
 --
 
-DROP TABLE IF EXISTS `migrate_map_{name}`;
-/*!40101 SET @saved_cs_client     = @@character_set_client */;
+{drop_map}/*!40101 SET @saved_cs_client     = @@character_set_client */;
 /*!40101 SET character_set_client = utf8 */;
-CREATE TABLE `migrate_map_{name}` (
+CREATE TABLE {if_not_exists}`{map}` (
     `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
     {sources}
     `destid1` int(10) unsigned DEFAULT NULL,
@@ -891,95 +474,3265 @@ CREATE TABLE `migrate_map_{name}` (
     `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
     PRIMARY KEY (`source_ids_hash`),
     {source_keys}
-) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+) {options} COMMENT='Mappings from source identifier value(s) to destination…';
 /*!40101 SET character_set_client = @saved_cs_client */;
 
 --
--- Table structure for table `migrate_message_{name}`
+-- Table structure for table `{message}`
 --
 
-DROP TABLE IF EXISTS `migrate_message_{name}`;
-/*!40101 SET @saved_cs_client     = @@character_set_client */;
+{drop_message}/*!40101 SET @saved_cs_client     = @@character_set_client */;
 /*!40101 SET character_set_client = utf8 */;
-CREATE TABLE `migrate_message_{name}` (
+CREATE TABLE {if_not_exists}`{message}` (
     `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
     `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
     `level` int(10) unsigned NOT NULL DEFAULT 1,
     `message` mediumtext NOT NULL,
     PRIMARY KEY (`msgid`)
-) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+) {options} COMMENT='Messages generated during a migration process';
 /*!40101 SET character_set_client = @saved_cs_client */;
 "#,
-            name = self.name,
+            map = config.map_table(self.name),
+            message = config.message_table(self.name),
             sources = sources,
-            source_keys = source_keys
+            source_keys = source_keys,
+            options = options,
+            drop_map = Self::drop(&config.map_table(self.name), config),
+            drop_message = Self::drop(&config.message_table(self.name), config),
+            if_not_exists = Self::if_not_exists(config),
         );
     }
+
+    // Matches the tables Drupal's SQLite driver creates for the migration.
+    fn create_sqlite(&self, config: &MigrationConfig) -> String {
+        let sources = (1..self.sources + 1)
+            .map(|i| format!("\"sourceid{}\" varchar(255) NOT NULL,", i))
+            .collect::<Vec<_>>()
+            .join("\n    ");
+        let source_keys = (1..self.sources + 1)
+            .map(|i| format!("\"sourceid{}\"", i))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"
+--
+-- Table structure for table "{map}"
+--
+
+{drop_map}CREATE TABLE {if_not_exists}"{map}" (
+    "source_ids_hash" varchar(64) NOT NULL,
+    {sources}
+    "destid1" INTEGER DEFAULT NULL,
+    "source_row_status" INTEGER NOT NULL DEFAULT 0,
+    "rollback_action" INTEGER NOT NULL DEFAULT 0,
+    "last_imported" INTEGER NOT NULL DEFAULT 0,
+    "hash" varchar(64) DEFAULT NULL,
+    PRIMARY KEY ("source_ids_hash")
+);
+CREATE INDEX {if_not_exists}"{map}_source" ON "{map}" ({source_keys});
+
+--
+-- Table structure for table "{message}"
+--
+
+{drop_message}CREATE TABLE {if_not_exists}"{message}" (
+    "msgid" INTEGER PRIMARY KEY AUTOINCREMENT,
+    "source_ids_hash" varchar(64) NOT NULL,
+    "level" INTEGER NOT NULL DEFAULT 1,
+    "message" TEXT NOT NULL
+);
+"#,
+            map = config.map_table(self.name),
+            message = config.message_table(self.name),
+            sources = sources,
+            source_keys = source_keys,
+            drop_map = Self::drop(&config.map_table(self.name), config),
+            drop_message = Self::drop(&config.message_table(self.name), config),
+            if_not_exists = Self::if_not_exists(config),
+        )
+    }
 }
 
-fn create_tables_preamble() -> String {
+fn create_tables_preamble(config: &MigrationConfig) -> String {
     [
         MigrateMapTable {
-            name: "fedora_users",
+            name: "users",
             sources: 1,
         },
         MigrateMapTable {
-            name: "fedora_files",
+            name: "files",
             sources: 3,
         },
         MigrateMapTable {
-            name: "fedora_media",
+            name: "media",
             sources: 2,
         },
         MigrateMapTable {
-            name: "fedora_media_fields",
+            name: "media_fields",
             sources: 2,
         },
         MigrateMapTable {
-            name: "fedora_media_revisions",
+            name: "media_revisions",
             sources: 3,
         },
         MigrateMapTable {
-            name: "fedora_nodes",
+            name: "nodes",
             sources: 1,
         },
     ]
     .iter()
-    .map(|table| table.to_string())
+    .map(|table| table.create(config))
     .collect::<Vec<_>>()
     .join("\n")
 }
 
-pub fn generate_sql(input: &Path, dest: &Path) {
-    let mut file = fs::File::create(dest.join("migrate.sql")).unwrap();
-    file.write_all(&create_tables_preamble().as_bytes())
-        .unwrap();
-    write_tables(&input, file).unwrap();
+// Summary of the input produced by validate().
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub rows: IndexMap<String, usize>, // Number of rows in each CSV file.
+    pub unresolved: Vec<UnresolvedReference>, // References to rows that do not exist.
+    pub duplicates: Vec<DuplicateSourceId>, // Rows which would overwrite an earlier row.
+    pub skipped: Vec<Error>,           // Rows which could not be parsed, if continue_on_error.
+    pub warnings: Vec<Warning>, // Rows which are not migrated as they are, e.g. reserved users.
 }
 
-#[cfg(test)]
-mod tests {
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.unresolved.is_empty() && self.duplicates.is_empty()
+    }
+}
 
-    #[test]
-    fn serialize() {
-        let values = vec!["namespace:123"];
-        let expected = r#"a:1:{i:0;s:13:"namespace:123";}"#.to_string();
-        let result = super::serialize(&values);
-        assert_eq!(result, expected);
-        let values = vec!["namespace:123", "DC"];
-        let expected = r#"a:2:{i:0;s:13:"namespace:123";i:1;s:2:"DC";}"#.to_string();
-        let result = super::serialize(&values);
-        assert_eq!(result, expected);
+#[derive(Debug)]
+pub struct UnresolvedReference {
+    pub file: String,            // CSV file of the row containing the reference.
+    pub source_ids: Vec<String>, // Source ids of the row containing the reference.
+    pub entity: &'static str,    // Type of the entity being referenced, e.g. user.
+    pub reference: Vec<String>,  // Source ids of the entity being referenced.
+}
+
+impl fmt::Display for UnresolvedReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({}) references unknown {} ({})",
+            self.file,
+            self.source_ids.join(", "),
+            self.entity,
+            self.reference.join(", ")
+        )
     }
+}
 
-    #[test]
-    fn source_hash() {
-        let values = vec!["vcu:38191", "JPG"];
-        let result = super::serialize(&values);
-        let expected = r#"a:2:{i:0;s:9:"vcu:38191";i:1;s:3:"JPG";}"#.to_string();
-        assert_eq!(result, expected);
-        let expected = "000004fd2f49c175d5642673755c3ee43f90b5eebad2694ac52eda44496c611f";
-        let result = super::hash(&result);
-        assert_eq!(result, expected);
+fn validate_rows<T>(
+    path: &Path,
+    ids: SharedTableIdMaps,
+    config: &MigrationConfig,
+    report: &mut ValidationReport,
+) -> Result<()>
+where
+    T: SourceRow,
+{
+    // Duplicates are reported rather than failing so they can all be listed at once.
+    let file = T::file_name(&config.file_names);
+    let csv = T::csv(path, config)?;
+    let (((mut map, hashes), skipped, warnings), duplicates) =
+        MigrateMap::<T>::read(&path.join(file), csv, config)?;
+    if let Some(limit) = config.limit {
+        map.drain(limit.min(map.len())..);
+    }
+    report.duplicates.extend(duplicates);
+    report.skipped.extend(skipped);
+    report.warnings.extend(warnings);
+    let map = MigrateMap {
+        map,
+        hashes,
+        ids: ids.clone(),
+        processed: Cell::default(),
+    };
+    // The ids are added before checking the references as nodes refer to other nodes.
+    insert_ids(&ids, T::id(), map.ids(config))?;
+    report.rows.insert(file.to_string(), map.map.len());
+    for row in map.map.values() {
+        for (id, reference) in row.references(config) {
+            let found = config
+                .reserved_uid(&id, &reference)
+                .or_else(|| map.lookup(&id, &reference));
+            if found.is_none() {
+                report.unresolved.push(UnresolvedReference {
+                    file: file.to_string(),
+                    source_ids: row.source_ids().iter().map(|s| s.to_string()).collect(),
+                    entity: id.entity(),
+                    reference: reference.iter().map(|s| s.to_string()).collect(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+// Parses every CSV file and checks that all the users, media, etc. they refer
+// to exist, without generating any SQL.
+pub fn validate(input: &Path) -> Result<ValidationReport> {
+    validate_with_config(input, &MigrationConfig::default())
+}
+
+pub fn validate_with_config(input: &Path, config: &MigrationConfig) -> Result<ValidationReport> {
+    config.validate()?;
+    let input = InputDirectory::open(input, config)?;
+    let input = input.path();
+    let ids = SharedTableIdMaps::new(RefCell::new(TableIdMaps::new()));
+    let mut report = ValidationReport::default();
+    validate_rows::<UserRow>(input, ids.clone(), config, &mut report)?;
+    validate_rows::<FileRow>(input, ids.clone(), config, &mut report)?;
+    validate_rows::<MediaRow>(input, ids.clone(), config, &mut report)?;
+    validate_rows::<MediaRevisionMigrateMapRow>(input, ids.clone(), config, &mut report)?;
+    validate_rows::<NodeRow>(input, ids, config, &mut report)?;
+    Ok(report)
+}
+
+// The id assigned to each row keyed by the hash of its source ids, as found in
+// the source_ids_hash column of the migration mapping tables, for each of uid,
+// fid, mid, vid and nid.
+pub fn dump_id_maps(input: &Path) -> Result<serde_json::Value> {
+    dump_id_maps_with_config(input, &MigrationConfig::default())
+}
+
+pub fn dump_id_maps_with_config(
+    input: &Path,
+    config: &MigrationConfig,
+) -> Result<serde_json::Value> {
+    config.validate()?;
+    let input = InputDirectory::open(input, config)?;
+    let input = input.path();
+    let ids = SharedTableIdMaps::default();
+    load::<UserRow>(input, ids.clone(), config)?;
+    load::<FileRow>(input, ids.clone(), config)?;
+    load::<MediaRow>(input, ids.clone(), config)?;
+    load::<MediaRevisionRow>(input, ids.clone(), config)?;
+    load::<NodeRow>(input, ids.clone(), config)?;
+    let ids = ids.borrow();
+    Ok(serde_json::Value::Object(
+        ids.iter()
+            .map(|(id, map)| {
+                let map = map
+                    .iter()
+                    .map(|(hash, index)| (hash.clone(), serde_json::Value::from(*index)))
+                    .collect();
+                (id.column().to_string(), serde_json::Value::Object(map))
+            })
+            .collect(),
+    ))
+}
+
+// Writes dump_id_maps() to id_maps.json.
+pub fn generate_id_maps_with_config(
+    input: &Path,
+    dest: &Path,
+    config: &MigrationConfig,
+) -> Result<()> {
+    let id_maps = dump_id_maps_with_config(input, config)?;
+    write_output(dest, "id_maps.json", config, |writer| {
+        serde_json::to_writer_pretty(&mut *writer, &id_maps).map_err(std::io::Error::from)?;
+        Ok(writeln!(writer)?)
+    })
+}
+
+// One row per source row in the order they are migrated, with the source ids
+// joined by '|' and the id the row is given.
+fn write_id_map<T>(
+    map: &MigrateMap<T>,
+    dest: &Path,
+    name: &str,
+    config: &MigrationConfig,
+) -> Result<()>
+where
+    T: SourceRow,
+{
+    let file = format!("{}_id_map.csv", name);
+    write_output(dest, &file, config, |writer| {
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["source_ids", "destid"])?;
+        for (row, destid) in map.map.values().zip(map.ids(config).values()) {
+            csv.write_record(&[row.source_ids().join("|"), destid.to_string()])?;
+        }
+        Ok(csv.flush()?)
+    })
+}
+
+// Writes an id map CSV for each entity, e.g. users_id_map.csv, which unlike the
+// migration mapping tables can be diffed across runs or read without a database.
+pub fn generate_id_map_csvs_with_config(
+    input: &Path,
+    dest: &Path,
+    config: &MigrationConfig,
+) -> Result<()> {
+    config.validate()?;
+    let input = InputDirectory::open(input, config)?;
+    let input = input.path();
+    let ids = SharedTableIdMaps::default();
+    write_id_map(
+        &load::<UserRow>(input, ids.clone(), config)?,
+        dest,
+        "users",
+        config,
+    )?;
+    write_id_map(
+        &load::<FileRow>(input, ids.clone(), config)?,
+        dest,
+        "files",
+        config,
+    )?;
+    write_id_map(
+        &load::<MediaRow>(input, ids.clone(), config)?,
+        dest,
+        "media",
+        config,
+    )?;
+    write_id_map(
+        &load::<MediaRevisionRow>(input, ids.clone(), config)?,
+        dest,
+        "media_revisions",
+        config,
+    )?;
+    write_id_map(&load::<NodeRow>(input, ids, config)?, dest, "nodes", config)
+}
+
+// Reads a CSV file mapping values, e.g. models, to taxonomy term ids, where the
+// first column is the value and the second the term id.
+pub fn read_term_map(path: &Path) -> Result<IndexMap<String, usize>> {
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let mut terms = IndexMap::new();
+    for record in reader.deserialize() {
+        let (value, tid): (String, usize) = record?;
+        terms.insert(value, tid);
+    }
+    Ok(terms)
+}
+
+// Reads a CSV file mapping media to the file of their thumbnail, where the
+// columns are the pid and dsid of the media followed by the pid, dsid and
+// version of the thumbnail in files.csv.
+pub fn read_thumbnail_map(path: &Path) -> Result<ThumbnailMap> {
+    let mut reader = ReaderBuilder::new().has_headers(true).from_path(path)?;
+    let mut thumbnails = IndexMap::new();
+    for record in reader.deserialize() {
+        let (pid, dsid, thumbnail_pid, thumbnail_dsid, thumbnail_version): (
+            String,
+            String,
+            String,
+            String,
+            String,
+        ) = record?;
+        thumbnails.insert(
+            (pid, dsid),
+            (thumbnail_pid, thumbnail_dsid, thumbnail_version),
+        );
+    }
+    Ok(thumbnails)
+}
+
+// Name of the output file as written by write_output().
+fn output_name(name: &str, config: &MigrationConfig) -> String {
+    if config.compress {
+        format!("{}.gz", name)
+    } else {
+        name.to_string()
+    }
+}
+
+// Create the given output file, or the file with a '.gz' extension compressed
+// as it is written if configured to.
+fn write_output<F, R>(dest: &Path, name: &str, config: &MigrationConfig, write: F) -> Result<R>
+where
+    F: FnOnce(&mut dyn Write) -> Result<R>,
+{
+    if config.compress {
+        let file = fs::File::create(dest.join(output_name(name, config)))?;
+        let encoder = GzEncoder::new(file, Compression::default());
+        let mut writer = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, encoder);
+        let result = write(&mut writer)?;
+        writer.flush()?;
+        writer.get_mut().try_finish()?;
+        Ok(result)
+    } else {
+        let file = fs::File::create(dest.join(name))?;
+        let mut writer = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, file);
+        let result = write(&mut writer)?;
+        writer.flush()?;
+        Ok(result)
+    }
+}
+
+pub fn generate_sql(input: &Path, dest: &Path) -> Result<Stats> {
+    generate_sql_with_config(input, dest, &MigrationConfig::default())
+}
+
+pub fn generate_sql_with_config(
+    input: &Path,
+    dest: &Path,
+    config: &MigrationConfig,
+) -> Result<Stats> {
+    config.validate()?;
+    let input = InputDirectory::open(input, config)?;
+    let input = input.path();
+    let (stats, outputs) = if config.split_output {
+        write_split_tables(input, dest, config)?
+    } else {
+        let stats = write_output(dest, "migrate.sql", config, |writer| {
+            generate_sql_to(input, writer, config)
+        })?;
+        (stats, vec![output_name("migrate.sql", config)])
+    };
+    write_manifest(dest, &outputs)?;
+    Ok(stats)
+}
+
+// Lists the SHA-256 of each of the output files as they are on disk, i.e.
+// compressed if they are, in the format of sha256sum so the files can be
+// checked once transferred with `sha256sum -c manifest.sha256`.
+fn write_manifest(dest: &Path, outputs: &[String]) -> Result<()> {
+    let mut manifest = String::new();
+    for name in outputs {
+        let mut file = fs::File::open(dest.join(name))?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; 1 << 16];
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.input(&buffer[..read]);
+        }
+        manifest.push_str(&format!("{}  {}\n", hasher.result_str(), name));
+    }
+    Ok(fs::write(dest.join("manifest.sha256"), manifest)?)
+}
+
+// Writes the SQL of migrate.sql to the given writer, e.g. the stdin of a mysql
+// client, rather than to a file. The writer is flushed but not buffered, and
+// split_output and compress are ignored as there is only the one writer.
+pub fn generate_sql_to(
+    input: &Path,
+    mut writer: &mut dyn Write,
+    config: &MigrationConfig,
+) -> Result<Stats> {
+    config.validate()?;
+    let input = InputDirectory::open(input, config)?;
+    let stats = write_sql(input.path(), &mut writer, config)?;
+    writer.flush()?;
+    Ok(stats)
+}
+
+fn write_sql(input: &Path, writer: &mut impl Write, config: &MigrationConfig) -> Result<Stats> {
+    if config.emit_map_tables {
+        writer.write_all(create_tables_preamble(config).as_bytes())?;
+    }
+    writer.write_all(transaction_start(config).as_bytes())?;
+    let stats = write_tables(input, writer, config)?;
+    writer.write_all(transaction_end(config).as_bytes())?;
+    writer.write_all(analyze_tables(&stats, config).as_bytes())?;
+    Ok(stats)
+}
+
+// Statements before and after all of the rows, foreign key checks can not be
+// changed within a transaction in SQLite so they surround it.
+fn transaction_start(config: &MigrationConfig) -> String {
+    let mut statements = String::new();
+    if config.disable_foreign_key_checks {
+        statements.push_str(match config.dialect {
+            Dialect::MySQL => "\nSET FOREIGN_KEY_CHECKS=0;\n",
+            Dialect::SQLite => "\nPRAGMA foreign_keys=OFF;\n",
+        });
+    }
+    if config.transactions == Transactions::Single {
+        statements.push_str(match config.dialect {
+            Dialect::MySQL => "\nSTART TRANSACTION;\n",
+            Dialect::SQLite => "\nBEGIN;\n",
+        });
+    }
+    statements
+}
+
+fn transaction_end(config: &MigrationConfig) -> String {
+    let mut statements = String::new();
+    if config.transactions == Transactions::Single {
+        statements.push_str("\nCOMMIT;\n");
+    }
+    if config.disable_foreign_key_checks {
+        statements.push_str(match config.dialect {
+            Dialect::MySQL => "SET FOREIGN_KEY_CHECKS=1;\n",
+            Dialect::SQLite => "PRAGMA foreign_keys=ON;\n",
+        });
+    }
+    statements
+}
+
+// The index statistics are stale after a large import, which makes the first
+// queries slow until they are refreshed. Only the tables with rows are analyzed.
+fn analyze_tables(stats: &Stats, config: &MigrationConfig) -> String {
+    if !config.analyze_tables {
+        return String::new();
+    }
+    let statement = match config.dialect {
+        Dialect::MySQL => "ANALYZE TABLE",
+        Dialect::SQLite => "ANALYZE",
+    };
+    let mut statements = "\n".to_string();
+    for (table, _) in stats.tables.iter().filter(|(_, rows)| **rows > 0) {
+        statements.push_str(&format!(
+            "{} {};\n",
+            statement,
+            config.dialect.identifier(table)
+        ));
+    }
+    statements
+}
+
+// Same as generate_sql() but the SQL is returned rather than written to a file.
+pub fn generate_sql_string(input: &Path) -> Result<String> {
+    generate_sql_string_with_config(input, &MigrationConfig::default())
+}
+
+pub fn generate_sql_string_with_config(input: &Path, config: &MigrationConfig) -> Result<String> {
+    let mut buffer = Vec::new();
+    generate_sql_to(input, &mut buffer, config)?;
+    String::from_utf8(buffer)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error).into())
+}
+
+pub fn generate_rollback(input: &Path, dest: &Path) -> Result<()> {
+    generate_rollback_with_config(input, dest, &MigrationConfig::default())
+}
+
+pub fn generate_rollback_with_config(
+    input: &Path,
+    dest: &Path,
+    config: &MigrationConfig,
+) -> Result<()> {
+    config.validate()?;
+    let input = InputDirectory::open(input, config)?;
+    write_output(dest, "rollback.sql", config, |mut writer| {
+        write_rollback(input.path(), &mut writer, config)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::{Read, Seek, SeekFrom};
+    use std::sync::Arc;
+
+    // Helper to create a CSV file with the given content.
+    fn csv_file(content: &[u8]) -> fs::File {
+        let mut csv = tempfile::tempfile().unwrap();
+        csv.write_all(content).unwrap();
+        csv.seek(SeekFrom::Start(0)).unwrap();
+        csv
+    }
+
+    // A complete set of CSV files, where the revision refers to an unknown user and file.
+    fn input_directory() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        write(
+            "users.csv",
+            "name,pass,mail,status,timezone,language\nfoo,,,1,UTC,en\n",
+        );
+        write(
+            "files.csv",
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,0\n",
+        );
+        write(
+            "media.csv",
+            &format!("{}a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n", media),
+        );
+        write(
+            "media_revisions.csv",
+            &format!("{}a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,bar\n", media),
+        );
+        write(
+            "nodes.csv",
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a:1,0,A,,,0,Active,foo,,\n",
+        );
+        dir
+    }
+
+    #[test]
+    fn serialize() {
+        let values = vec!["namespace:123"];
+        let expected = r#"a:1:{i:0;s:13:"namespace:123";}"#.to_string();
+        let result = super::serialize(&values);
+        assert_eq!(result, expected);
+        let values = vec!["namespace:123", "DC"];
+        let expected = r#"a:2:{i:0;s:13:"namespace:123";i:1;s:2:"DC";}"#.to_string();
+        let result = super::serialize(&values);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn serialize_multibyte() {
+        // PHP gives the length of strings in bytes rather than characters, which
+        // every source_ids_hash depends on.
+        assert_eq!(super::serialize(&["café:1"]), r#"a:1:{i:0;s:7:"café:1";}"#);
+        assert_eq!(
+            super::source_ids_hash(&["café:1"]),
+            "22bb20732cce5af09a36bdcb1be63c8a145fd4691d11a273973e73cb4c6f13ac"
+        );
+        assert_eq!(
+            super::serialize(&["漢字:1", "OBJ"]),
+            r#"a:2:{i:0;s:8:"漢字:1";i:1;s:3:"OBJ";}"#
+        );
+        assert_eq!(
+            super::source_ids_hash(&["漢字:1", "OBJ"]),
+            "cd21b59df8265edb58125c4001aba352a864a9580cb7e35001e8b6a0f8714b71"
+        );
+    }
+
+    #[test]
+    fn serialize_quotes() {
+        // PHP does not escape quotes inside strings, the length delimits them.
+        assert_eq!(
+            super::serialize(&[r#"say"hi""#]),
+            r#"a:1:{i:0;s:7:"say"hi"";}"#
+        );
+        assert_eq!(
+            super::source_ids_hash(&[r#"say"hi""#]),
+            "d568fc3992c2b78ef187dc376f578a76153ae9aecd44fad85d19180e656c46f8"
+        );
+    }
+
+    #[test]
+    fn sql_string() {
+        assert_eq!(super::sql_string("plain"), "'plain'");
+        assert_eq!(
+            super::sql_string("O'Brien collection"),
+            r"'O\'Brien collection'"
+        );
+        assert_eq!(super::sql_string(r"C:\path"), r"'C:\\path'");
+        assert_eq!(super::sql_string("line\nbreak"), r"'line\nbreak'");
+        assert_eq!(super::sql_string("nul\0byte"), r"'nul\0byte'");
+    }
+
+    #[test]
+    fn source_hash() {
+        let values = vec!["vcu:38191", "JPG"];
+        let result = super::serialize(&values);
+        let expected = r#"a:2:{i:0;s:9:"vcu:38191";i:1;s:3:"JPG";}"#.to_string();
+        assert_eq!(result, expected);
+        let expected = "000004fd2f49c175d5642673755c3ee43f90b5eebad2694ac52eda44496c611f";
+        let result = super::hash(&result);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn migrate_map_values() {
+        let user = UserRow {
+            name: "foo,bar:1".to_string(),
+            pass: String::new(),
+            mail: String::new(),
+            status: String::new(),
+            timezone: String::new(),
+            language: String::new(),
+            login: None,
+            init: None,
+            roles: None,
+            source_id: "foo,bar:1".to_string(),
+        };
+        let hash = user.source_ids_hash();
+        let map = MigrateUserMap {
+            map: vec![(hash.clone(), user)].into_iter().collect(),
+            hashes: vec![(hash.clone(), "abc".to_string())]
+                .into_iter()
+                .collect(),
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let config = MigrationConfig::default();
+        let values = map
+            .migrate_map_values(&config)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(values.len(), 1);
+        assert!(values[0].starts_with(&format!("('{}','foo,bar:1',2,0,", hash)));
+        assert!(values[0].ends_with(",'abc')"));
+    }
+
+    #[test]
+    fn row_error_context() {
+        let csv = csv_file(b"name,pass,status,timezone,language\nfoo,bar,1,UTC,en\n");
+        let error = MigrateUserMap::map(
+            Path::new("/input/users.csv"),
+            &csv,
+            &MigrationConfig::default(),
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.to_string(), "users.csv:2: missing field `mail`");
+    }
+
+    #[test]
+    fn validate_langcode() {
+        let mut config = MigrationConfig::default();
+        assert!(config.validate().is_ok());
+        config.langcode = "zh-hans".to_string();
+        assert!(config.validate().is_ok());
+        config.langcode = "".to_string();
+        assert!(config.validate().is_err());
+        config.langcode = "f".to_string();
+        assert!(config.validate().is_err());
+        config.langcode = "fr'; DROP".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn row_hash() {
+        let csv =
+            csv_file(b"name,pass,mail,status,timezone,language\nfoo,,foo@example.com,1,UTC,en\n");
+        let (map, hashes) =
+            MigrateUserMap::map(Path::new("users.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let key = map.keys().next().unwrap();
+        let expected = super::hash(concat!(
+            r#"a:6:{s:4:"name";s:3:"foo";s:4:"pass";s:0:"";s:4:"mail";s:15:"foo@example.com";"#,
+            r#"s:6:"status";s:1:"1";s:8:"timezone";s:3:"UTC";s:8:"language";s:2:"en";}"#
+        ));
+        assert_eq!(hashes[key], expected);
+    }
+
+    #[test]
+    fn batched_inserts() {
+        let table = Table {
+            name: "users".to_string(),
+            columns: columns(&["uid"]),
+            values: Box::new(
+                vec!["(1)", "(2)", "(3)"]
+                    .into_iter()
+                    .map(|value| Ok(value.to_string())),
+            ),
+        };
+        let config = MigrationConfig {
+            batch_size: 2,
+            ..MigrationConfig::default()
+        };
+        let mut output = Vec::new();
+        table.dump(&mut output, &config).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("INSERT INTO `users` (uid) VALUES\n  (1),\n  (2);\n"));
+        assert!(output.contains("INSERT INTO `users` (uid) VALUES\n  (3);\n"));
+    }
+
+    #[test]
+    fn empty_tables() {
+        let csv =
+            csv_file(b"pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n");
+        let (map, hashes) = MigrateMediaRevisionMapExcludingMigration::map(
+            Path::new("media_revisions.csv"),
+            &csv,
+            &MigrationConfig::default(),
+        )
+        .unwrap();
+        let map = MigrateMediaRevisionMapExcludingMigration {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let mut output = Vec::new();
+        map.dump(&mut output, &MigrationConfig::default()).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn dump_to_buffer() {
+        let csv = csv_file(b"name,pass,mail,status,timezone,language\nfoo,,,1,UTC,en\n");
+        let (map, hashes) =
+            MigrateUserMap::map(Path::new("users.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateUserMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let mut output = Vec::new();
+        map.dump(&mut output, &MigrationConfig::default()).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("LOCK TABLES `users` WRITE;"));
+        assert!(output.contains("LOCK TABLES `users_field_data` WRITE;"));
+        assert!(output.contains("LOCK TABLES `migrate_map_fedora_users` WRITE;"));
+    }
+
+    #[test]
+    fn deterministic_uuids() {
+        let config = MigrationConfig {
+            deterministic_uuids: true,
+            ..MigrationConfig::default()
+        };
+        let users = || {
+            let csv = csv_file(b"name,pass,mail,status,timezone,language\nfoo,,,1,UTC,en\n");
+            let (map, hashes) =
+                MigrateUserMap::map(Path::new("users.csv"), &csv, &MigrationConfig::default())
+                    .unwrap();
+            let map = MigrateUserMap {
+                map,
+                hashes,
+                ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+                processed: Cell::default(),
+            };
+            let values = map
+                .tables(&config)
+                .remove(0)
+                .values
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            values
+        };
+        assert_eq!(users(), users());
+        assert_ne!(
+            uuid("user", "hash", &MigrationConfig::default()),
+            uuid("user", "hash", &MigrationConfig::default())
+        );
+    }
+
+    #[test]
+    fn uuid_namespace() {
+        let mut config = MigrationConfig {
+            deterministic_uuids: true,
+            ..MigrationConfig::default()
+        };
+        let default = uuid("user", "hash", &config);
+        assert_eq!(
+            default,
+            uuid::Uuid::new_v5(&UUID_NAMESPACE, "user:hash".as_bytes())
+        );
+        config.uuid_namespace = Some("9a3c1f4e-2b7d-4e8a-b5c6-0d1e2f3a4b5c".to_string());
+        assert!(config.validate().is_ok());
+        let site = uuid("user", "hash", &config);
+        assert_ne!(site, default);
+        assert_eq!(site, uuid("user", "hash", &config));
+        config.uuid_namespace = Some("not a uuid".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn generated_at() {
+        let config = MigrationConfig {
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let csv = csv_file(b"name,pass,mail,status,timezone,language\nfoo,,,1,UTC,en\n");
+        let (map, hashes) =
+            MigrateUserMap::map(Path::new("users.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateUserMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let values = map
+            .tables(&config)
+            .remove(1)
+            .values
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec!["(2,'en','foo',1600000000,0,0,1,NULL,NULL,1,'UTC')"]
+        );
+    }
+
+    #[test]
+    fn login_init() {
+        let config = MigrationConfig {
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let csv = csv_file(
+            b"name,pass,mail,status,timezone,language,login,init\n\
+              foo,,new@example.com,1,UTC,en,1500000000,old@example.com\n\
+              bar,,bar@example.com,1,UTC,en,,\n",
+        );
+        let (map, hashes) =
+            MigrateUserMap::map(Path::new("users.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateUserMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let values = map
+            .tables(&config)
+            .remove(1)
+            .values
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                "(2,'en','foo',1600000000,1500000000,1500000000,1,'new@example.com','old@example.com',1,'UTC')",
+                "(3,'en','bar',1600000000,0,0,1,'bar@example.com','bar@example.com',1,'UTC')"
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_references() {
+        let dir = input_directory();
+        let report = validate(dir.path()).unwrap();
+        assert_eq!(report.rows["media_revisions.csv"], 1);
+        assert_eq!(
+            report
+                .unresolved
+                .iter()
+                .map(|reference| reference.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "media_revisions.csv (a:1, OBJ, OBJ.1) references unknown user (bar)",
+                "media_revisions.csv (a:1, OBJ, OBJ.1) references unknown file (a:1, OBJ, OBJ.1)",
+            ]
+        );
+        assert!(!report.is_valid());
+        let invalid = MigrationConfig {
+            langcode: String::new(),
+            ..MigrationConfig::default()
+        };
+        assert!(matches!(
+            validate_with_config(dir.path(), &invalid),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_user() {
+        let csv = csv_file(
+            b"pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+              a:1,0,A,,,0,Active,bar,,\n",
+        );
+        let (map, hashes) =
+            MigrateNodeMap::map(Path::new("nodes.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateNodeMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let error = map
+            .dump(&mut Vec::new(), &MigrationConfig::default())
+            .err()
+            .unwrap();
+        assert!(matches!(error, Error::UnknownUser(ref user) if user == "bar"));
+        let config = MigrationConfig {
+            unknown_users_as_admin: true,
+            ..MigrationConfig::default()
+        };
+        assert!(map.dump(&mut Vec::new(), &config).is_ok());
+    }
+
+    #[test]
+    fn unknown_media() {
+        let csv = csv_file(
+            b"pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+              a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,admin\n",
+        );
+        let (map, hashes) = MigrateMediaRevisionMapExcludingMigration::map(
+            Path::new("media_revisions.csv"),
+            &csv,
+            &MigrationConfig::default(),
+        )
+        .unwrap();
+        let map = MigrateMediaRevisionMapExcludingMigration {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let error = map
+            .dump(&mut Vec::new(), &MigrationConfig::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Unknown media 'OBJ' (a:1) not found in media.csv"
+        );
+    }
+
+    #[test]
+    fn custom_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MigrationConfig {
+            file_names: FileNames {
+                users: "accounts.csv".to_string(),
+                ..FileNames::default()
+            },
+            ..MigrationConfig::default()
+        };
+        let headers = [
+            ("accounts.csv", UserRow::headers()),
+            ("files.csv", FileRow::headers()),
+            ("media.csv", MediaRow::headers()),
+            ("media_revisions.csv", MediaRevisionRow::headers()),
+            ("nodes.csv", NodeRow::headers()),
+        ];
+        for (name, headers) in &headers {
+            fs::write(dir.path().join(name), headers.join(",")).unwrap();
+        }
+        assert!(valid_source_directory(dir.path(), &config).is_ok());
+        assert!(valid_source_directory(dir.path(), &MigrationConfig::default()).is_err());
+    }
+
+    #[test]
+    fn headers() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("users.csv"),
+            "name,pass,status,timezone,language,role",
+        )
+        .unwrap();
+        let error = check_headers::<UserRow>(dir.path(), &MigrationConfig::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            error,
+            "'users.csv' has missing columns: mail; unexpected columns: role"
+        );
+        fs::write(
+            dir.path().join("users.csv"),
+            "language,name,pass,mail,status,timezone",
+        )
+        .unwrap();
+        assert_eq!(
+            check_headers::<UserRow>(dir.path(), &MigrationConfig::default()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn sqlite_dialect() {
+        let table = Table {
+            name: "users".to_string(),
+            columns: columns(&["uid", "name"]),
+            values: Box::new(vec![Ok("(2,'O''Brien')".to_string())].into_iter()),
+        };
+        let config = MigrationConfig {
+            dialect: Dialect::SQLite,
+            ..MigrationConfig::default()
+        };
+        let mut buffer = Vec::new();
+        table.dump(&mut buffer, &config).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        assert!(output.contains(
+            "BEGIN;\nINSERT INTO \"users\" (uid,name) VALUES\n  (2,'O''Brien');\nCOMMIT;\n"
+        ));
+        assert!(!output.contains("LOCK TABLES"));
+        assert_eq!(Dialect::SQLite.quote("O'Brien"), "'O''Brien'");
+        assert!(!create_tables_preamble(&config).contains("/*!"));
+    }
+
+    #[test]
+    fn rollback() {
+        let dir = input_directory();
+        let mut buffer = Vec::new();
+        write_rollback(dir.path(), &mut buffer, &MigrationConfig::default()).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+        let statements = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            statements[0],
+            format!(
+                "DELETE FROM `migrate_map_fedora_media_revisions` WHERE source_ids_hash IN ('{}');",
+                source_ids_hash(&["a:1", "OBJ", "OBJ.1"])
+            )
+        );
+        // The node table is deleted from last of all the node tables, which
+        // are deleted after the media which refer to them.
+        let position = |statement: &str| statements.iter().position(|s| *s == statement).unwrap();
+        assert_eq!(
+            position("DELETE FROM `node` WHERE nid BETWEEN 1 AND 1;") + 1,
+            position(&format!(
+                "DELETE FROM `migrate_map_fedora_files` WHERE source_ids_hash IN ('{}');",
+                source_ids_hash(&["a:1", "OBJ", "OBJ.0"])
+            ))
+        );
+        assert!(
+            position("DELETE FROM `media` WHERE mid BETWEEN 1 AND 1;")
+                < position(&format!(
+                    "DELETE FROM `migrate_map_fedora_nodes` WHERE source_ids_hash IN ('{}');",
+                    source_ids_hash(&["a:1"])
+                ))
+        );
+        assert!(statements.contains(&"DELETE FROM `media_revision` WHERE mid BETWEEN 1 AND 1;"));
+        assert_eq!(
+            statements.last().unwrap(),
+            &"DELETE FROM `users` WHERE uid BETWEEN 2 AND 2;"
+        );
+    }
+
+    #[test]
+    fn file_usage() {
+        let csv = csv_file(
+            b"pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+              a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,admin\n",
+        );
+        let (map, hashes) = MigrateMediaRevisionMapExcludingMigration::map(
+            Path::new("media_revisions.csv"),
+            &csv,
+            &MigrationConfig::default(),
+        )
+        .unwrap();
+        let mut ids = TableIdMaps::new();
+        ids.insert(
+            IdMaps::MID,
+            vec![(source_ids_hash(&["a:1", "OBJ"]), 5)]
+                .into_iter()
+                .collect(),
+        );
+        ids.insert(
+            IdMaps::FID,
+            vec![(source_ids_hash(&["a:1", "OBJ", "OBJ.1"]), 7)]
+                .into_iter()
+                .collect(),
+        );
+        let map = MigrateMediaRevisionMapExcludingMigration {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(ids)),
+            processed: Cell::default(),
+        };
+        let config = MigrationConfig::default();
+        let table = map.tables(&config).remove(2);
+        assert_eq!(table.name, "file_usage");
+        let values = table.values.collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(values, vec!["(7,'file','media',5,1)"]);
+    }
+
+    #[test]
+    fn media_file_fields() {
+        let csv = csv_file(
+            b"pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+              a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n\
+              a:2,OBJ,OBJ.0,audio,0,0,,audio/mpeg,a.mp3,admin\n",
+        );
+        let (map, hashes) =
+            MigrateMediaMap::map(Path::new("media.csv"), &csv, &MigrationConfig::default())
+                .unwrap();
+        let mut ids = TableIdMaps::new();
+        ids.insert(
+            IdMaps::FID,
+            vec![
+                (source_ids_hash(&["a:1", "OBJ", "OBJ.0"]), 3),
+                (source_ids_hash(&["a:2", "OBJ", "OBJ.0"]), 4),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let map = MigrateMediaMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(ids)),
+            processed: Cell::default(),
+        };
+        let mut config = MigrationConfig::default();
+        config
+            .media_file_fields
+            .insert("audio".to_string(), "field_media_audio_file".to_string());
+        let tables = media_file_tables(&map, &config, false);
+        assert_eq!(
+            tables
+                .iter()
+                .map(|table| table.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["media__field_media_audio_file", "media__field_media_image"]
+        );
+        let values = tables
+            .into_iter()
+            .flat_map(|table| table.values)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec!["(2,2,'audio',0,'en',0,4)", "(1,1,'image',0,'en',0,3)"]
+        );
+        config
+            .media_file_fields
+            .insert("video".to_string(), "field-video".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn member_of() {
+        let csv = csv_file(
+            b"pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+              a:root,0,Root,,,0,Active,admin,,\n\
+              a:1,0,A,,,0,Active,admin,,a:root\n\
+              a:2,0,B,,,0,Active,admin,,a:root|a:1\n",
+        );
+        let (map, hashes) =
+            MigrateNodeMap::map(Path::new("nodes.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateNodeMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let config = MigrationConfig::default();
+        let values = map
+            .field_tables(&config, "field_member_of", &["target_id"], |node| {
+                map.member_of(node, &config)
+            })
+            .remove(0)
+            .values
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                "(2,2,'islandora_object',0,'en',0,1)",
+                "(3,3,'islandora_object',0,'en',0,1)",
+                "(3,3,'islandora_object',0,'en',1,2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn model() {
+        let csv = csv_file(
+            b"pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+              a:1,0,A,,Image,0,Active,admin,,\n\
+              a:2,0,B,,,0,Active,admin,,\n\
+              a:3,0,C,,Book,0,Active,admin,,\n",
+        );
+        let (map, hashes) =
+            MigrateNodeMap::map(Path::new("nodes.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateNodeMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("models.csv"), "model,tid\nImage,7\n").unwrap();
+        let terms = read_term_map(&dir.path().join("models.csv")).unwrap();
+        let values = map
+            .map
+            .values()
+            .map(|node| MigrateNodeMap::model(node, &terms).map_err(|error| error.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Ok(vec!["7".to_string()]),
+                Ok(vec![]),
+                Err("Unknown model 'Book' has no taxonomy term".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pid_field() {
+        let csv = csv_file(
+            b"pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+              a:1,0,A,,,0,Active,admin,,\n",
+        );
+        let (map, hashes) =
+            MigrateNodeMap::map(Path::new("nodes.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateNodeMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let config = MigrationConfig {
+            pid_field: "field_fedora_pid".to_string(),
+            ..MigrationConfig::default()
+        };
+        let tables = map.tables(&config);
+        let table = tables
+            .into_iter()
+            .find(|table| table.name == "node_revision__field_fedora_pid")
+            .unwrap();
+        assert_eq!(table.columns.last().unwrap(), "field_fedora_pid_value");
+        let values = table.values.collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(values, vec!["(1,1,'islandora_object',0,'en',0,'a:1')"]);
+    }
+
+    #[test]
+    fn node_status() {
+        let csv = csv_file(
+            b"pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+              a:1,0,A,,,0,Active,admin,,\n\
+              a:2,0,B,,,0,Inactive,admin,,\n",
+        );
+        let (map, hashes) =
+            MigrateNodeMap::map(Path::new("nodes.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateNodeMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let config = MigrationConfig::default();
+        let values = map
+            .tables(&config)
+            .remove(2)
+            .values
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                "(1,1,'islandora_object','en',1,1,'A',0,0,1,0,1)",
+                "(2,2,'islandora_object','en',0,1,'B',0,0,1,0,1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn weight() {
+        let csv = csv_file(
+            b"pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+              a:1,0,A,,,0,Active,admin,,\n\
+              a:2,0,B,3,,0,Active,admin,,\n\
+              a:3,0,C,-2,,0,Active,admin,,\n",
+        );
+        let (map, _) =
+            MigrateNodeMap::map(Path::new("nodes.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let weights = map
+            .values()
+            .map(|node| node.weight().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(weights, vec![0, 3, -2]);
+    }
+
+    #[test]
+    fn invalid_weight() {
+        let dir = input_directory();
+        fs::write(
+            dir.path().join("nodes.csv"),
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a:1,0,A,,,0,Active,foo,,\n\
+             a:2,0,B,first,,0,Active,foo,,\n",
+        )
+        .unwrap();
+        // Rejected when the nodes are read, before any SQL is written.
+        let mut config = MigrationConfig::default();
+        assert_eq!(
+            validate_with_config(dir.path(), &config)
+                .unwrap_err()
+                .to_string(),
+            "Invalid weight 'first' of node (a:2) is not an integer"
+        );
+        config.continue_on_error = true;
+        let report = validate_with_config(dir.path(), &config).unwrap();
+        assert!(matches!(
+            report.skipped.as_slice(),
+            [Error::InvalidInteger {
+                column: "weight",
+                ..
+            }]
+        ));
+        assert_eq!(report.rows["nodes.csv"], 1);
+    }
+
+    #[test]
+    fn password_hash() {
+        let hash = super::password_hash("secret", &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            hash,
+            "$S$E.2U.1EE/w7HOwz7v/LdQhdS8o7ARcXxzVFU0YNn9.YjFbrxD38q"
+        );
+        let config = MigrationConfig {
+            passwords: Passwords::Plaintext,
+            deterministic_uuids: true,
+            ..MigrationConfig::default()
+        };
+        let user = |pass: &str| UserRow {
+            name: "foo".to_string(),
+            pass: pass.to_string(),
+            mail: String::new(),
+            status: String::new(),
+            timezone: String::new(),
+            language: String::new(),
+            login: None,
+            init: None,
+            roles: None,
+            source_id: "foo".to_string(),
+        };
+        let pass = user("secret").pass(&config).unwrap();
+        assert!(pass.starts_with("$S$E"));
+        assert_eq!(pass.len(), 55);
+        // Salted independently of the deterministic uuids.
+        assert_ne!(user("secret").pass(&config).unwrap(), pass);
+        assert_eq!(user("").pass(&config), Some(String::new()));
+        let config = MigrationConfig {
+            passwords: Passwords::Hashed,
+            ..MigrationConfig::default()
+        };
+        assert_eq!(user("$S$E...").pass(&config), Some("$S$E...".to_string()));
+        assert_eq!(user("secret").pass(&MigrationConfig::default()), None);
+    }
+
+    #[test]
+    fn user_status() {
+        let csv = csv_file(
+            b"name,pass,mail,status,timezone,language\n\
+              foo,,foo@example.com,,UTC,en\n\
+              bar,,bar@example.com,0,UTC,en\n\
+              baz,,baz@example.com,yes,UTC,en\n",
+        );
+        let (map, _) =
+            MigrateUserMap::map(Path::new("users.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let statuses = map
+            .values()
+            .map(|user| user.status().map_err(|error| error.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                Ok(1),
+                Ok(0),
+                Err("Invalid status 'yes' of user 'baz' is neither 0 nor 1".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamp() {
+        let mut config = MigrationConfig::default();
+        assert_eq!(config.timestamp("a:1", "1395414245").unwrap(), "1395414245");
+        assert_eq!(
+            config.timestamp("a:1", "2014-03-21T15:04:05Z").unwrap(),
+            "1395414245"
+        );
+        assert_eq!(
+            config
+                .timestamp("a:1", "2014-03-21T11:04:05-04:00")
+                .unwrap(),
+            "1395414245"
+        );
+        assert_eq!(
+            config
+                .timestamp("a:1", "21/03/2014 15:04:05")
+                .unwrap_err()
+                .to_string(),
+            "Invalid date '21/03/2014 15:04:05' of 'a:1' could not be parsed"
+        );
+        config.date_format = Some("%d/%m/%Y %H:%M:%S".to_string());
+        assert_eq!(
+            config.timestamp("a:1", "21/03/2014 15:04:05").unwrap(),
+            "1395414245"
+        );
+        assert_eq!(config.timestamp("a:1", "").unwrap(), "NULL");
+        config.date_format = Some("%Q".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn nullable() {
+        assert_eq!(sql_nullable_int(""), "NULL");
+        assert_eq!(sql_nullable_int("12"), "12");
+        let csv = csv_file(
+            b"pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+              a:1,OBJ,OBJ.0,,image/png,a.png,public://a.png,admin,,\n",
+        );
+        let (map, hashes) =
+            MigrateFileMap::map(Path::new("files.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let map = MigrateFileMap {
+            map,
+            hashes,
+            ids: SharedTableIdMaps::new(RefCell::new(TableIdMaps::new())),
+            processed: Cell::default(),
+        };
+        let config = MigrationConfig {
+            generated_at: Some(1_600_000_000),
+            deterministic_uuids: true,
+            ..MigrationConfig::default()
+        };
+        let values = map
+            .tables(&config)
+            .remove(0)
+            .values
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(values[0].ends_with(",'image/png',NULL,1,NULL,1600000000)"));
+    }
+
+    #[test]
+    fn gzip_input() {
+        let dir = input_directory();
+        for name in &["users.csv", "media_revisions.csv"] {
+            let path = dir.path().join(name);
+            let mut encoder = flate2::write::GzEncoder::new(
+                fs::File::create(dir.path().join(format!("{}.gz", name))).unwrap(),
+                flate2::Compression::default(),
+            );
+            encoder.write_all(&fs::read(&path).unwrap()).unwrap();
+            encoder.finish().unwrap();
+            fs::remove_file(path).unwrap();
+        }
+        assert!(valid_source_directory(dir.path(), &MigrationConfig::default()).is_ok());
+        let report = validate(dir.path()).unwrap();
+        assert_eq!(report.rows["users.csv"], 1);
+        assert_eq!(report.rows["media_revisions.csv"], 1);
+    }
+
+    #[test]
+    fn compress() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = MigrationConfig {
+            compress: true,
+            ..MigrationConfig::default()
+        };
+        write_output(dir.path(), "migrate.sql", &config, |writer| {
+            Ok(writer.write_all(b"BEGIN;\n")?)
+        })
+        .unwrap();
+        assert!(!dir.path().join("migrate.sql").exists());
+        let mut output = String::new();
+        GzDecoder::new(fs::File::open(dir.path().join("migrate.sql.gz")).unwrap())
+            .read_to_string(&mut output)
+            .unwrap();
+        assert_eq!(output, "BEGIN;\n");
+    }
+
+    #[test]
+    fn split_output() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let config = MigrationConfig {
+            split_output: true,
+            ..MigrationConfig::default()
+        };
+        generate_sql_with_config(dir.path(), dest.path(), &config).unwrap();
+        let mut files = fs::read_dir(dest.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                "00_preamble.sql",
+                "01_users.sql",
+                "02_files.sql",
+                "03_nodes.sql",
+                "04_media.sql",
+                "05_media_revisions.sql",
+                "manifest.sha256"
+            ]
+        );
+        let users = fs::read_to_string(dest.path().join("01_users.sql")).unwrap();
+        assert!(users.contains("INSERT INTO `users_field_data`"));
+        assert!(!users.contains("INSERT INTO `file_managed`"));
+    }
+
+    #[test]
+    fn sql_string_output() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            deterministic_uuids: true,
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        generate_sql_with_config(dir.path(), dest.path(), &config).unwrap();
+        assert_eq!(
+            sql,
+            fs::read_to_string(dest.path().join("migrate.sql")).unwrap()
+        );
+    }
+
+    #[test]
+    fn progress() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let reports = Arc::new(std::sync::Mutex::new(vec![]));
+        let config = MigrationConfig {
+            progress: Some(Progress::new({
+                let reports = reports.clone();
+                move |entity, processed, total| {
+                    reports
+                        .lock()
+                        .unwrap()
+                        .push((entity.to_string(), processed, total))
+                }
+            })),
+            ..MigrationConfig::default()
+        };
+        generate_sql_string_with_config(dir.path(), &config).unwrap();
+        let reports = reports.lock().unwrap();
+        // One user serialized for each of users, users_field_data, user__roles,
+        // which it has none of, and the migrate map.
+        assert_eq!(
+            reports
+                .iter()
+                .filter(|(entity, _, _)| entity == "user")
+                .collect::<Vec<_>>(),
+            vec![
+                &("user".to_string(), 1, 4),
+                &("user".to_string(), 2, 4),
+                &("user".to_string(), 3, 4),
+                &("user".to_string(), 4, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn limit() {
+        let dir = input_directory();
+        fs::write(
+            dir.path().join("users.csv"),
+            "name,pass,mail,status,timezone,language\nbar,,,1,UTC,en\nfoo,,,1,UTC,en\n",
+        )
+        .unwrap();
+        let config = MigrationConfig {
+            limit: Some(1),
+            ..MigrationConfig::default()
+        };
+        let report = validate_with_config(dir.path(), &config).unwrap();
+        assert_eq!(report.rows["users.csv"], 1);
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config)
+                .unwrap_err()
+                .to_string(),
+            "Unknown users not found in users.csv: foo"
+        );
+    }
+
+    #[test]
+    fn duplicate_source_ids() {
+        let dir = input_directory();
+        fs::write(
+            dir.path().join("users.csv"),
+            "name,pass,mail,status,timezone,language\nfoo,,,1,UTC,en\nfoo,,,0,UTC,en\n",
+        )
+        .unwrap();
+        let report = validate(dir.path()).unwrap();
+        assert_eq!(
+            report
+                .duplicates
+                .iter()
+                .map(|duplicate| duplicate.to_string())
+                .collect::<Vec<_>>(),
+            vec!["users.csv:3: Duplicate source ids (foo) of an earlier row"]
+        );
+        assert!(!report.is_valid());
+        assert_eq!(
+            generate_sql_string(dir.path()).unwrap_err().to_string(),
+            "users.csv:3: Duplicate source ids (foo) of an earlier row"
+        );
+    }
+
+    #[test]
+    fn stats() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let dest = tempfile::tempdir().unwrap();
+        let stats = generate_sql(dir.path(), dest.path()).unwrap();
+        assert_eq!(
+            stats.entities,
+            vec![
+                ("user".to_string(), 1),
+                ("file".to_string(), 1),
+                ("media".to_string(), 1),
+                ("media revision".to_string(), 1),
+                ("node".to_string(), 1)
+            ]
+            .into_iter()
+            .collect::<IndexMap<_, _>>()
+        );
+        assert_eq!(stats.tables["users_field_data"], 1);
+        assert_eq!(stats.tables["migrate_map_fedora_media_revisions"], 0);
+        assert_eq!(stats.tables["node__field_member_of"], 0);
+    }
+
+    #[test]
+    fn uri_scheme() {
+        let csv = csv_file(
+            b"pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+              a:1,OBJ,OBJ.0,0,image/png,a.png,fedora/a.png,admin,,0\n\
+              a:2,OBJ,OBJ.0,0,image/png,b.png,/fedora/b.png,admin,,0\n\
+              a:3,OBJ,OBJ.0,0,image/png,c.png,private://c.png,admin,,0\n",
+        );
+        let (map, _) =
+            MigrateFileMap::map(Path::new("files.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let mut config = MigrationConfig {
+            uri_scheme: Some("public://".to_string()),
+            ..MigrationConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        let uris = map
+            .values()
+            .map(|file| file.uri(&config))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            uris,
+            vec![
+                "public://fedora/a.png",
+                "public://fedora/b.png",
+                "private://c.png"
+            ]
+        );
+        config.uri_scheme = Some("public".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn files_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("fedora")).unwrap();
+        fs::write(dir.path().join("fedora/a.png"), "abc").unwrap();
+        let csv = csv_file(
+            b"pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+              a:1,OBJ,OBJ.0,0,image/png,a.png,public://fedora/a.png,admin,,\n\
+              a:2,OBJ,OBJ.0,0,image/png,b.png,public://fedora/b.png,admin,,\n",
+        );
+        let (map, _) =
+            MigrateFileMap::map(Path::new("files.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let mut config = MigrationConfig {
+            files_directory: Some(dir.path().to_path_buf()),
+            ..MigrationConfig::default()
+        };
+        let a = &map[0];
+        assert_eq!(
+            a.sha1(&config).unwrap(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(a.size(&config).unwrap(), "3");
+        let b = &map[1];
+        assert!(b
+            .sha1(&config)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Missing file"));
+        config.missing_files = MissingFiles::Skip;
+        assert_eq!(b.size(&config).unwrap(), "");
+    }
+
+    #[test]
+    fn mime_type() {
+        assert_eq!(guess_mime_type("a.PNG"), Some("image/png"));
+        assert_eq!(guess_mime_type("a.tar.zip"), Some("application/zip"));
+        assert_eq!(guess_mime_type("a.unknown"), None);
+        assert_eq!(guess_mime_type("a"), None);
+        let csv = csv_file(
+            b"pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+              a:1,OBJ,OBJ.0,0,,a.jpg,public://a.jpg,admin,,0\n\
+              a:2,OBJ,OBJ.0,0,image/png,b.jpg,public://b.jpg,admin,,0\n\
+              a:3,OBJ,OBJ.0,0,,c,public://c,admin,,0\n",
+        );
+        let (map, _) =
+            MigrateFileMap::map(Path::new("files.csv"), &csv, &MigrationConfig::default()).unwrap();
+        let types = map
+            .values()
+            .map(|file| file.mime_type())
+            .collect::<Vec<_>>();
+        assert_eq!(types, vec!["image/jpeg", "image/png", ""]);
+    }
+
+    #[test]
+    fn reserved_users() {
+        let dir = input_directory();
+        let mut config = MigrationConfig {
+            unknown_users_as_admin: true,
+            ..MigrationConfig::default()
+        };
+        let map = MigrateUserMap::new(dir.path(), SharedTableIdMaps::default(), &config).unwrap();
+        assert_eq!(map.uid("admin", &config).unwrap(), 1);
+        config.reserved_users = vec![("root".to_string(), 1), ("anonymous".to_string(), 0)]
+            .into_iter()
+            .collect();
+        assert_eq!(map.uid("anonymous", &config).unwrap(), 0);
+        assert_eq!(map.uid("root", &config).unwrap(), 1);
+        assert_eq!(map.uid("bar", &config).unwrap(), 1);
+        // Unknown users are owned by admin wherever it is mapped to.
+        config.reserved_users.insert("admin".to_string(), 0);
+        assert_eq!(map.uid("bar", &config).unwrap(), 0);
+        config.reserved_users.shift_remove("admin");
+        config.unknown_users_as_admin = false;
+        assert!(map.uid("admin", &config).is_err());
+    }
+
+    #[test]
+    fn offsets() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let mut config = MigrationConfig {
+            offsets: Offsets {
+                users: 50,
+                nodes: 10000,
+                ..Offsets::default()
+            },
+            ..MigrationConfig::default()
+        };
+        let users = MigrateUserMap::new(dir.path(), SharedTableIdMaps::default(), &config).unwrap();
+        assert_eq!(users.ids(&config).values().collect::<Vec<_>>(), vec![&50]);
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        // Nodes reference their owner through the offset uid.
+        assert!(sql.contains("(10000,10000,'islandora_object','en',1,50,'A',"));
+        config.offsets.files = 0;
+        assert!(config.validate().is_err());
+        config.offsets.files = 1;
+        config.offsets.users = 1; // Would clash with admin.
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn delimiter() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            deterministic_uuids: true,
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let expected = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        for entry in fs::read_dir(dir.path()).unwrap() {
+            let path = entry.unwrap().path();
+            let csv = fs::read_to_string(&path).unwrap();
+            fs::write(&path, csv.replace(',', ";")).unwrap();
+        }
+        assert!(valid_source_directory(dir.path(), &config).is_err());
+        let config = MigrationConfig {
+            delimiter: b';',
+            ..config
+        };
+        assert!(valid_source_directory(dir.path(), &config).is_ok());
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config).unwrap(),
+            expected
+        );
+        assert!(MigrationConfig {
+            quote: b';',
+            ..config
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn media_revisions_csv() {
+        let dir = input_directory();
+        let mut csv = String::new();
+        MediaRevisionRow::csv(dir.path(), &MigrationConfig::default())
+            .unwrap()
+            .read_to_string(&mut csv)
+            .unwrap();
+        // Media come first so their vids match their mids.
+        assert_eq!(
+            csv,
+            "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+             a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n\
+             a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,bar\n"
+        );
+    }
+
+    #[test]
+    fn media_revisions_line_endings() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user";
+        fs::write(
+            dir.path().join("media.csv"),
+            format!("{}\na:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin", media),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("media_revisions.csv"),
+            format!(
+                "{}\r\na:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,bar\r\n",
+                media
+            ),
+        )
+        .unwrap();
+        let mut csv = String::new();
+        MediaRevisionRow::csv(dir.path(), &MigrationConfig::default())
+            .unwrap()
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(
+            csv,
+            format!(
+                "{}\na:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n\
+                 a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,bar\n",
+                media
+            )
+        );
+    }
+
+    #[test]
+    fn stdin() {
+        let dir = input_directory();
+        fs::remove_file(dir.path().join("files.csv")).unwrap();
+        let mut config = MigrationConfig::default();
+        config.file_names.files = STDIN.to_string();
+        assert!(config.validate().is_ok());
+        assert!(valid_source_directory(dir.path(), &config).is_ok());
+        config.file_names.nodes = STDIN.to_string();
+        assert!(config.validate().is_err());
+        config.file_names.nodes = "nodes.csv".to_string();
+        config.file_names.media = STDIN.to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn id_maps() {
+        let dir = input_directory();
+        let config = MigrationConfig {
+            unknown_users_as_admin: true,
+            ..MigrationConfig::default()
+        };
+        let id_maps = dump_id_maps_with_config(dir.path(), &config).unwrap();
+        assert_eq!(id_maps["uid"][source_ids_hash(&["foo"])], 2);
+        assert_eq!(id_maps["fid"][source_ids_hash(&["a:1", "OBJ", "OBJ.0"])], 1);
+        assert_eq!(id_maps["mid"][source_ids_hash(&["a:1", "OBJ"])], 1);
+        assert_eq!(id_maps["vid"][source_ids_hash(&["a:1", "OBJ", "OBJ.1"])], 2);
+        assert_eq!(id_maps["nid"][source_ids_hash(&["a:1"])], 1);
+    }
+
+    #[test]
+    fn id_map_csvs() {
+        let dir = input_directory();
+        let dest = tempfile::tempdir().unwrap();
+        let config = MigrationConfig {
+            unknown_users_as_admin: true,
+            ..MigrationConfig::default()
+        };
+        generate_id_map_csvs_with_config(dir.path(), dest.path(), &config).unwrap();
+        let read = |name: &str| fs::read_to_string(dest.path().join(name)).unwrap();
+        assert_eq!(read("users_id_map.csv"), "source_ids,destid\nfoo,2\n");
+        assert_eq!(
+            read("media_revisions_id_map.csv"),
+            "source_ids,destid\na:1|OBJ|OBJ.0,1\na:1|OBJ|OBJ.1,2\n"
+        );
+        assert_eq!(read("nodes_id_map.csv"), "source_ids,destid\na:1,1\n");
+    }
+
+    #[test]
+    fn builder() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let migration = MigrationBuilder::new(dir.path())
+            .node_type("my_type")
+            .langcode("fr")
+            .deterministic_uuids(true)
+            .generated_at(1_600_000_000)
+            .build()
+            .unwrap();
+        let config = MigrationConfig {
+            node_type: "my_type".to_string(),
+            langcode: "fr".to_string(),
+            deterministic_uuids: true,
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        assert_eq!(
+            migration.to_sql_string().unwrap(),
+            generate_sql_string_with_config(dir.path(), &config).unwrap()
+        );
+        let dest = tempfile::tempdir().unwrap();
+        migration.write_to(dest.path()).unwrap();
+        assert!(dest.path().join("migrate.sql").exists());
+        assert!(MigrationBuilder::new(dir.path())
+            .langcode("")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn media_revisions_merge() {
+        let dir = input_directory();
+        let config = MigrationConfig::default();
+        let ((map, hashes), _, _) =
+            MigrateMediaRevisionMapExcludingMigration::parse(dir.path(), &config).unwrap();
+        let files = ParsedFiles::parse(dir.path(), &config).unwrap();
+        let (merged, merged_hashes) = &files.media_revisions;
+        assert_eq!(
+            merged.keys().collect::<Vec<_>>(),
+            map.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(merged_hashes, &hashes);
+        // The latest revision must not also appear in media_revisions.csv.
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+                     a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        assert!(matches!(
+            ParsedFiles::parse(dir.path(), &config),
+            Err(Error::DuplicateMediaRevision { ref ids, .. }) if ids == &["a:1", "OBJ", "OBJ.0"]
+        ));
+    }
+
+    #[test]
+    fn moderation_states() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let mut config = MigrationConfig::default();
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(!output.contains("content_moderation_state"));
+        config
+            .moderation_states
+            .insert("Active".to_string(), "published".to_string());
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains(
+            "INSERT INTO `content_moderation_state_field_data` (id,revision_id,langcode,uid,\
+             workflow,moderation_state,content_entity_type_id,content_entity_id,\
+             content_entity_revision_id,default_langcode,revision_translation_affected) VALUES\n  \
+             (1,1,'en',2,'editorial','published','node',1,1,1,1);"
+        ));
+        let mut rollback = Vec::new();
+        write_rollback(dir.path(), &mut rollback, &config).unwrap();
+        assert!(String::from_utf8(rollback)
+            .unwrap()
+            .contains("DELETE FROM `content_moderation_state` WHERE id BETWEEN 1 AND 1;"));
+        config.offsets.moderation_states = 5;
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains("(5,5,'en',2,'editorial','published','node',1,1,1,1);"));
+        config.offsets.moderation_states = 0;
+        assert!(config.validate().is_err());
+        config.offsets.moderation_states = 1;
+        config.moderation_states.clear();
+        config
+            .moderation_states
+            .insert("Inactive".to_string(), "archived".to_string());
+        assert!(matches!(
+            generate_sql_string_with_config(dir.path(), &config),
+            Err(Error::UnknownState(state)) if state == "Active"
+        ));
+    }
+
+    #[test]
+    fn path_alias() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("nodes.csv"),
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a'b:1,0,A,,,0,Active,foo,,\n",
+        )
+        .unwrap();
+        let config = MigrationConfig {
+            path_alias: Some("/object/{slug}/{pid}".to_string()),
+            ..MigrationConfig::default()
+        };
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains(
+            "INSERT INTO `path_alias_revision` (id,revision_id,langcode,path,alias,status,\
+             revision_default) VALUES\n  (1,1,'en','/node/1','/object/a-b-1/a\\'b:1',1,1);"
+        ));
+        // Aliases have their own ids, apart from the nodes they are for.
+        let mut config = config;
+        config.offsets.nodes = 10;
+        config.offsets.path_aliases = 3;
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains("(3,3,'en','/node/10','/object/a-b-1/a\\'b:1',1,1);"));
+        let mut rollback = Vec::new();
+        write_rollback(dir.path(), &mut rollback, &config).unwrap();
+        assert!(String::from_utf8(rollback)
+            .unwrap()
+            .contains("DELETE FROM `path_alias` WHERE id BETWEEN 3 AND 3;"));
+        for template in &["object/{pid}", "/object"] {
+            assert!(MigrationConfig {
+                path_alias: Some(template.to_string()),
+                ..MigrationConfig::default()
+            }
+            .validate()
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn promote_sticky() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig::default();
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains("(1,1,'islandora_object','en',1,2,'A',0,0,1,0,1);"));
+        let nodes = "pid,created_date,label,weight,model,modified_date,state,user,display_hint,\
+                     parents,promote,sticky\n";
+        fs::write(
+            dir.path().join("nodes.csv"),
+            format!("{}a:1,0,A,,,0,Active,foo,,,no,TRUE\n", nodes),
+        )
+        .unwrap();
+        assert_eq!(validate_headers(dir.path(), &config), Ok(()));
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains("(1,1,'islandora_object','en',1,2,'A',0,0,0,1,1);"));
+        assert!(output.contains("(1,1,'en',1,2,'A',0,0,0,1,1);"));
+        fs::write(
+            dir.path().join("nodes.csv"),
+            format!("{}a:1,0,A,,,0,Active,foo,,,maybe,\n", nodes),
+        )
+        .unwrap();
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config)
+                .unwrap_err()
+                .to_string(),
+            "Invalid promote 'maybe' of 'a:1' is neither true nor false"
+        );
+    }
+
+    #[test]
+    fn insert_mode() {
+        let dump = |insert_mode| {
+            let table = Table {
+                name: "users".to_string(),
+                columns: columns(&["uid", "name"]),
+                values: Box::new(vec![Ok("(1,'a')".to_string())].into_iter()),
+            };
+            let config = MigrationConfig {
+                insert_mode,
+                ..MigrationConfig::default()
+            };
+            let mut output = Vec::new();
+            table.dump(&mut output, &config).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        assert!(dump(InsertMode::Insert)
+            .contains("INSERT INTO `users` (uid,name) VALUES\n  (1,'a');\n"));
+        assert!(dump(InsertMode::Replace)
+            .contains("REPLACE INTO `users` (uid,name) VALUES\n  (1,'a');\n"));
+        assert!(dump(InsertMode::Upsert).contains(
+            "INSERT INTO `users` (uid,name) VALUES\n  (1,'a')\nON DUPLICATE KEY UPDATE name=VALUES(name);\n"
+        ));
+        assert!(MigrationConfig {
+            insert_mode: InsertMode::Upsert,
+            dialect: Dialect::SQLite,
+            ..MigrationConfig::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn single_transaction() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            transactions: Transactions::Single,
+            disable_foreign_key_checks: true,
+            ..MigrationConfig::default()
+        };
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(!output.contains("LOCK TABLES"));
+        assert!(!output.contains("commit;"));
+        let start = output
+            .find("\nSET FOREIGN_KEY_CHECKS=0;\n\nSTART TRANSACTION;\n")
+            .unwrap();
+        assert!(start > output.rfind("CREATE TABLE").unwrap());
+        assert!(start < output.find("INSERT INTO").unwrap());
+        assert!(output.ends_with("\nCOMMIT;\nSET FOREIGN_KEY_CHECKS=1;\n"));
+        let output =
+            generate_sql_string_with_config(dir.path(), &MigrationConfig::default()).unwrap();
+        assert!(!output.contains("START TRANSACTION"));
+        assert!(!output.contains("FOREIGN_KEY_CHECKS"));
+    }
+
+    #[test]
+    fn table_options() {
+        let preamble = create_tables_preamble(&MigrationConfig::default());
+        assert_eq!(
+            preamble
+                .matches(" ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT=")
+                .count(),
+            12
+        );
+        let config = MigrationConfig {
+            engine: "Aria".to_string(),
+            charset: "utf8".to_string(),
+            collation: Some("utf8_general_ci".to_string()),
+            ..MigrationConfig::default()
+        };
+        let preamble = create_tables_preamble(&config);
+        assert_eq!(
+            preamble
+                .matches(" ENGINE=Aria DEFAULT CHARSET=utf8 COLLATE=utf8_general_ci COMMENT=")
+                .count(),
+            12
+        );
+        assert!(MigrationConfig {
+            engine: "InnoDB; DROP TABLE users".to_string(),
+            ..MigrationConfig::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn keep_map_tables() {
+        for dialect in &[Dialect::MySQL, Dialect::SQLite] {
+            let mut config = MigrationConfig {
+                dialect: *dialect,
+                ..MigrationConfig::default()
+            };
+            let preamble = create_tables_preamble(&config);
+            assert_eq!(preamble.matches("DROP TABLE IF EXISTS").count(), 12);
+            assert!(!preamble.contains("IF NOT EXISTS"));
+            config.drop_existing_map_tables = false;
+            let preamble = create_tables_preamble(&config);
+            assert!(!preamble.contains("DROP TABLE"));
+            assert_eq!(preamble.matches("CREATE TABLE IF NOT EXISTS").count(), 12);
+        }
+    }
+
+    #[test]
+    fn migration_prefix() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            migration_prefix: "myimport".to_string(),
+            map_table_prefix: "site_migrate_map_".to_string(),
+            ..MigrationConfig::default()
+        };
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(!output.contains("fedora"));
+        assert!(output.contains("CREATE TABLE `site_migrate_map_myimport_nodes` ("));
+        assert!(output.contains("CREATE TABLE `migrate_message_myimport_nodes` ("));
+        assert!(output.contains("INSERT INTO `site_migrate_map_myimport_nodes` ("));
+        let mut rollback = Vec::new();
+        write_rollback(dir.path(), &mut rollback, &config).unwrap();
+        let rollback = String::from_utf8(rollback).unwrap();
+        assert!(rollback
+            .contains("DELETE FROM `site_migrate_map_myimport_users` WHERE source_ids_hash IN ("));
+        assert!(MigrationConfig {
+            migration_prefix: "my import".to_string(),
+            ..MigrationConfig::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn unknown_users() {
+        let dir = input_directory();
+        fs::write(
+            dir.path().join("nodes.csv"),
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a:1,0,A,,,0,Active,baz,,\n\
+             a:2,0,B,,,0,Active,bar,,\n",
+        )
+        .unwrap();
+        let error = generate_sql_string(dir.path()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown users not found in users.csv: bar, baz"
+        );
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            unknown_users_as_admin: true,
+            ..MigrationConfig::default()
+        };
+        assert!(generate_sql_string_with_config(dir.path(), &config).is_ok());
+    }
+
+    #[test]
+    fn orphaned_revisions() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(
+            dir.path().join("media_revisions.csv"),
+            format!(
+                "{}a:2,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n\
+                 a:2,OBJ,OBJ.1,image,0,0,,image/png,a.png,admin\n\
+                 a:1,TN,TN.0,image,0,0,,image/png,a.png,admin\n",
+                media
+            ),
+        )
+        .unwrap();
+        let error = generate_sql_string(dir.path()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Media revisions of media not found in media.csv: 'OBJ' (a:2), 'TN' (a:1)"
+        );
+    }
+
+    #[test]
+    fn revision_log_message() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let mut config = MigrationConfig::default();
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains(
+            "INSERT INTO `media_revision` (mid,vid,langcode,revision_user,revision_created,\
+             revision_default,revision_log_message) VALUES\n  \
+             (1,1,'en',1,0,1,'Migrated from Fedora version OBJ.0');"
+        ));
+        config.revision_log_message = Some("Fedora's {version}".to_string());
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains("(1,1,'en',1,0,1,'Fedora\\'s OBJ.0');"));
+        config.revision_log_message = None;
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains(
+            "INSERT INTO `media_revision` (mid,vid,langcode,revision_user,revision_created,\
+             revision_default) VALUES\n  (1,1,'en',1,0,1);"
+        ));
+    }
+
+    #[test]
+    fn node_revision_log() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let mut config = MigrationConfig::default();
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains(
+            "INSERT INTO `node_revision` (nid,vid,langcode,revision_uid,revision_timestamp,\
+             revision_default,revision_log) VALUES\n  \
+             (1,1,'en',2,0,1,'Migrated from Fedora object a:1');"
+        ));
+        config.node_revision_log = None;
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains(
+            "INSERT INTO `node_revision` (nid,vid,langcode,revision_uid,revision_timestamp,\
+             revision_default) VALUES\n  (1,1,'en',2,0,1);"
+        ));
+    }
+
+    #[test]
+    fn latest_version_only() {
+        assert!(newer_version("OBJ.10", "OBJ.9"));
+        assert!(!newer_version("OBJ.9", "OBJ.10"));
+        assert!(newer_version("OBJ.b", "OBJ.a"));
+        let dir = input_directory();
+        fs::write(
+            dir.path().join("files.csv"),
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.9,0,image/png,a.png,public://a9.png,foo,,0\n\
+             a:1,OBJ,OBJ.10,0,image/png,a.png,public://a10.png,foo,,0\n\
+             a:1,TN,TN.0,0,image/png,a.png,public://tn.png,foo,,0\n",
+        )
+        .unwrap();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(
+            dir.path().join("media.csv"),
+            format!("{}a:1,OBJ,OBJ.10,image,0,0,,image/png,a.png,admin\n", media),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("media_revisions.csv"),
+            format!("{}a:1,OBJ,OBJ.9,image,0,0,,image/png,a.png,bar\n", media),
+        )
+        .unwrap();
+        let config = MigrationConfig {
+            latest_version_only: true,
+            ..MigrationConfig::default()
+        };
+        let stats =
+            generate_sql_with_config(dir.path(), tempfile::tempdir().unwrap().path(), &config)
+                .unwrap();
+        assert_eq!(stats.entities["file"], 2);
+        assert_eq!(stats.entities["media revision"], 1);
+        assert_eq!(stats.tables["migrate_map_fedora_media_revisions"], 0);
+        let output = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(output.contains("'public://a10.png'"));
+        assert!(!output.contains("'public://a9.png'"));
+    }
+
+    #[test]
+    fn media_bundles() {
+        let config = MigrationConfig::default();
+        assert_eq!(config.media_bundle("image/png").unwrap(), "image");
+        assert_eq!(config.media_bundle("application/pdf").unwrap(), "document");
+        assert_eq!(config.media_bundle("application/xml").unwrap(), "file");
+        let csv = csv_file(
+            b"pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+              a:1,OBJ,OBJ.0,,0,0,,audio/mpeg,a.mp3,admin\n\
+              a:1,PDF,PDF.0,custom,0,0,,application/pdf,a.pdf,admin\n\
+              a:1,JPG,JPG.0,,0,0,,,a.jpg,admin\n",
+        );
+        let (map, _) = MigrateMediaMap::map(Path::new("media.csv"), &csv, &config).unwrap();
+        // Without a mime type it is guessed from the name.
+        assert_eq!(
+            map.values().map(|row| row.bundle()).collect::<Vec<_>>(),
+            vec!["audio", "custom", "image"]
+        );
+        assert_eq!(config.media_bundle("audio/ogg").unwrap(), "audio");
+        assert_eq!(config.media_bundle("video/quicktime").unwrap(), "video");
+        assert_eq!(config.media_bundle("image/tiff").unwrap(), "file");
+        let csv = || {
+            csv_file(
+                b"pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+                  a:1,OBJ,OBJ.0,,0,0,,model/stl,a.stl,admin\n",
+            )
+        };
+        assert_eq!(
+            MigrateMediaMap::map(Path::new("media.csv"), &csv(), &config)
+                .err()
+                .unwrap()
+                .to_string(),
+            "Unknown mime type 'model/stl' has no media bundle"
+        );
+        let mut config = MigrationConfig::default();
+        config
+            .media_bundles
+            .insert("*".to_string(), "file".to_string());
+        let (map, _) = MigrateMediaMap::map(Path::new("media.csv"), &csv(), &config).unwrap();
+        assert_eq!(map[0].bundle(), "file");
+    }
+
+    #[test]
+    fn continue_on_error() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        // One row has too many columns and another is not valid UTF-8.
+        fs::write(
+            dir.path().join("files.csv"),
+            &b"pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+               a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,0\n\
+               a:2,OBJ,OBJ.0,0,image/png,b.png,public://b.png,foo,,0,extra\n\
+               a:3,OBJ,OBJ.0,0,image/png,c\xff.png,public://c.png,foo,,0\n"[..],
+        )
+        .unwrap();
+        let mut config = MigrationConfig::default();
+        assert!(generate_sql_string_with_config(dir.path(), &config).is_err());
+        config.continue_on_error = true;
+        let output = tempfile::tempdir().unwrap();
+        let stats = generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        assert_eq!(stats.entities["file"], 1);
+        let lines = stats
+            .skipped
+            .iter()
+            .map(|error| match error {
+                Error::Row { file, line, .. } => (file.file_name().unwrap().to_owned(), *line),
+                _ => panic!("{}", error),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![("files.csv".into(), 3), ("files.csv".into(), 4)]
+        );
+        let report = validate_with_config(dir.path(), &config).unwrap();
+        assert_eq!(report.skipped.len(), 2);
+    }
+
+    #[test]
+    fn warnings() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("files.csv"),
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.0,0,,a.png,public://a.png,foo,,\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("nodes.csv"),
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a:1,0,A,,,0,Active,bar,,\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("media.csv"),
+            format!("{}a:1,OBJ,OBJ.0,image,0,0,,,a.png,foo\n", media),
+        )
+        .unwrap();
+        let config = MigrationConfig {
+            unknown_users_as_admin: true,
+            ..MigrationConfig::default()
+        };
+        let output = tempfile::tempdir().unwrap();
+        let stats = generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        assert_eq!(
+            stats
+                .warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "node (a:1): Unknown user 'bar' replaced by admin",
+                "file (a:1, OBJ, OBJ.0): No mime type, guessed 'image/png' from the name",
+                "file (a:1, OBJ, OBJ.0): No size, filesize left NULL",
+                "media (a:1, OBJ): No mime type, guessed 'image/png' from the name",
+            ]
+        );
+    }
+
+    #[test]
+    fn temp_directory() {
+        let dir = input_directory();
+        let temp = tempfile::tempdir().unwrap();
+        let mut config = MigrationConfig {
+            temp_directory: Some(temp.path().to_path_buf()),
+            ..MigrationConfig::default()
+        };
+        let mut csv = String::new();
+        MediaRevisionRow::csv(dir.path(), &config)
+            .unwrap()
+            .read_to_string(&mut csv)
+            .unwrap();
+        assert_eq!(csv.lines().count(), 3);
+        config.temp_directory = Some(temp.path().join("missing"));
+        assert!(matches!(
+            MediaRevisionRow::csv(dir.path(), &config),
+            Err(Error::IOError(_))
+        ));
+    }
+
+    #[test]
+    fn anonymous_user() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("nodes.csv"),
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a:1,0,A,,,0,Active,anonymous,,\n",
+        )
+        .unwrap();
+        let mut config = MigrationConfig::default();
+        assert!(generate_sql_string_with_config(dir.path(), &config).is_err());
+        config.reserved_users.insert("anonymous".to_string(), 0);
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(sql.contains("(1,1,'islandora_object','en',1,0,'A',"));
+    }
+
+    #[test]
+    fn reserved_user_rows() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("users.csv"),
+            "name,pass,mail,status,timezone,language\nadmin,,,1,UTC,en\nfoo,,,1,UTC,en\n",
+        )
+        .unwrap();
+        // Drupal already has an admin, so one in users.csv is not created again.
+        let config = MigrationConfig::default();
+        let map = MigrateUserMap::new(dir.path(), SharedTableIdMaps::default(), &config).unwrap();
+        assert_eq!(map.ids(&config).values().collect::<Vec<_>>(), vec![&2]);
+        let warning = "user (admin): Reserved user of uid 1, not migrated";
+        let output = tempfile::tempdir().unwrap();
+        let stats = generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        assert_eq!(stats.entities["user"], 1);
+        assert_eq!(
+            stats
+                .warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<_>>(),
+            vec![warning]
+        );
+        let report = validate(dir.path()).unwrap();
+        assert_eq!(report.rows["users.csv"], 1);
+        assert_eq!(report.warnings[0].to_string(), warning);
+    }
+
+    #[test]
+    fn thumbnails() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("files.csv"),
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,0\n\
+             a:1,TN,TN.0,0,image/jpeg,tn.jpg,public://tn.jpg,foo,,0\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("media.csv"),
+            format!(
+                "{}a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin\n\
+                 a:1,TN,TN.0,image,0,0,,image/jpeg,tn.jpg,admin\n",
+                media
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("thumbnails.csv"),
+            "pid,dsid,thumbnail_pid,thumbnail_dsid,thumbnail_version\na:1,OBJ,a:1,TN,TN.0\n",
+        )
+        .unwrap();
+        let mut config = MigrationConfig::default();
+        assert!(!generate_sql_string_with_config(dir.path(), &config)
+            .unwrap()
+            .contains("__thumbnail"));
+        config.thumbnails = Some(read_thumbnail_map(&dir.path().join("thumbnails.csv")).unwrap());
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        // Only the media with a thumbnail has a row, referring to the second file.
+        for table in &["media__thumbnail", "media_revision__thumbnail"] {
+            assert!(sql.contains(&format!(
+                "INSERT INTO `{}` (entity_id,revision_id,bundle,deleted,langcode,delta,thumbnail_target_id) VALUES\n  (1,1,'image',0,'en',0,2);",
+                table
+            )));
+        }
+        config.thumbnails = Some(
+            vec![(
+                ("a:1".to_string(), "OBJ".to_string()),
+                (
+                    "a:1".to_string(),
+                    "MISSING".to_string(),
+                    "MISSING.0".to_string(),
+                ),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        assert!(matches!(
+            generate_sql_string_with_config(dir.path(), &config),
+            Err(Error::UnknownFile { .. })
+        ));
+    }
+
+    #[test]
+    fn first_node_id() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig::default();
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(sql.contains("(1,1,'islandora_object','en',1,2,'A',"));
+        // The id maps, and so the migrate_map destids, agree with the rows.
+        let id_maps = dump_id_maps_with_config(dir.path(), &config).unwrap();
+        assert_eq!(id_maps["fid"][source_ids_hash(&["a:1", "OBJ", "OBJ.0"])], 1);
+        assert_eq!(id_maps["mid"][source_ids_hash(&["a:1", "OBJ"])], 1);
+        assert_eq!(id_maps["nid"][source_ids_hash(&["a:1"])], 1);
+    }
+
+    #[test]
+    fn quoted_bundle() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("media.csv"),
+            format!(
+                "{}a:1,OBJ,OBJ.0,Bob's image,0,0,,image/png,a.png,admin\n",
+                media
+            ),
+        )
+        .unwrap();
+        let config = MigrationConfig::default();
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        for row in &[
+            "(1,1,'Bob\\'s image','",
+            "(1,1,'Bob\\'s image','en',1,1,'a.png',",
+            "(1,1,'Bob\\'s image',0,'en',0,1)",
+        ] {
+            assert!(sql.contains(row), "{}", row);
+        }
+    }
+
+    #[test]
+    fn invalid_integer() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let files = "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n";
+        fs::write(
+            dir.path().join("files.csv"),
+            format!(
+                "{}a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,N/A\n",
+                files
+            ),
+        )
+        .unwrap();
+        let mut config = MigrationConfig::default();
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config)
+                .unwrap_err()
+                .to_string(),
+            "Invalid size 'N/A' of file (a:1, OBJ, OBJ.0) is not an integer"
+        );
+        config.continue_on_error = true;
+        assert!(matches!(
+            validate_with_config(dir.path(), &config)
+                .unwrap()
+                .skipped
+                .as_slice(),
+            [Error::InvalidInteger { column: "size", .. }]
+        ));
+        // Empty sizes are left NULL.
+        fs::write(
+            dir.path().join("files.csv"),
+            format!(
+                "{}a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,, \n",
+                files
+            ),
+        )
+        .unwrap();
+        config.continue_on_error = false;
+        assert!(generate_sql_string_with_config(dir.path(), &config).is_ok());
+    }
+
+    #[test]
+    fn no_map_tables() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            emit_map_tables: false,
+            ..MigrationConfig::default()
+        };
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(!sql.contains("migrate_"));
+        // The nodes still refer to their owner.
+        assert!(sql.contains("(1,1,'islandora_object','en',1,2,'A',"));
+        let mut rollback = Vec::new();
+        write_rollback(dir.path(), &mut rollback, &config).unwrap();
+        let rollback = String::from_utf8(rollback).unwrap();
+        assert!(rollback.contains("DELETE FROM `node` WHERE nid BETWEEN 1 AND 1;"));
+        assert!(!rollback.contains("migrate_"));
+        let output = tempfile::tempdir().unwrap();
+        let config = MigrationConfig {
+            split_output: true,
+            ..config
+        };
+        generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        assert!(!output.path().join("00_preamble.sql").exists());
+        assert!(output.path().join("01_users.sql").exists());
+    }
+
+    #[test]
+    fn generate_to_writer() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let config = MigrationConfig {
+            deterministic_uuids: true,
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let mut buffer = Vec::new();
+        let stats = generate_sql_to(dir.path(), &mut buffer, &config).unwrap();
+        assert_eq!(stats.entities["node"], 1);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            generate_sql_string_with_config(dir.path(), &config).unwrap()
+        );
+        let invalid = MigrationConfig {
+            langcode: String::new(),
+            ..config
+        };
+        assert!(matches!(
+            generate_sql_to(dir.path(), &mut Vec::new(), &invalid),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn analyze_tables() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let mut config = MigrationConfig::default();
+        assert!(!generate_sql_string_with_config(dir.path(), &config)
+            .unwrap()
+            .contains("ANALYZE"));
+        config.analyze_tables = true;
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        let footer = &sql[sql.rfind("commit;").unwrap()..];
+        assert!(footer.contains("\nANALYZE TABLE `node`;\n"));
+        assert!(footer.contains("\nANALYZE TABLE `migrate_map_fedora_nodes`;\n"));
+        // There are no media revisions.
+        assert!(!footer.contains("`migrate_map_fedora_media_revisions`"));
+        config.dialect = Dialect::SQLite;
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(sql.ends_with("ANALYZE \"media_revision__field_media_of\";\n"));
+        let output = tempfile::tempdir().unwrap();
+        config.split_output = true;
+        generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        let analyze = fs::read_to_string(output.path().join("06_analyze.sql")).unwrap();
+        assert!(analyze.contains("ANALYZE \"users\";"));
+    }
+
+    #[test]
+    fn media_revision_destids() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(
+            dir.path().join("media_revisions.csv"),
+            format!("{}a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,foo\n", media),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("files.csv"),
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,0\n\
+             a:1,OBJ,OBJ.1,0,image/png,a.png,public://b.png,foo,,0\n",
+        )
+        .unwrap();
+        let config = MigrationConfig::default();
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        // The revision is numbered after the media, in both tables.
+        assert!(sql.contains("(1,2,'en',2,0,1,"));
+        assert!(sql.contains(&format!(
+            "('{}','a:1','OBJ','OBJ.1',2,",
+            source_ids_hash(&["a:1", "OBJ", "OBJ.1"])
+        )));
+        let ids = SharedTableIdMaps::default();
+        let table = |id| {
+            vec![("a".to_string(), id)]
+                .into_iter()
+                .collect::<TableIdMap>()
+        };
+        insert_ids(&ids, IdMaps::VID, table(1)).unwrap();
+        insert_ids(&ids, IdMaps::VID, table(1)).unwrap();
+        assert_eq!(
+            insert_ids(&ids, IdMaps::VID, table(2))
+                .unwrap_err()
+                .to_string(),
+            "Inconsistent media revision id 2 of source ids hash 'a' which already has id 1"
+        );
+    }
+
+    #[test]
+    fn manifest() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        let output = tempfile::tempdir().unwrap();
+        let mut config = MigrationConfig::default();
+        generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        let mut hasher = Sha256::new();
+        hasher.input(&fs::read(output.path().join("migrate.sql")).unwrap());
+        assert_eq!(
+            fs::read_to_string(output.path().join("manifest.sha256")).unwrap(),
+            format!("{}  migrate.sql\n", hasher.result_str())
+        );
+        config.split_output = true;
+        config.compress = true;
+        generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+        let manifest = fs::read_to_string(output.path().join("manifest.sha256")).unwrap();
+        let names = manifest
+            .lines()
+            .map(|line| line.split_once("  ").unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "00_preamble.sql.gz",
+                "01_users.sql.gz",
+                "02_files.sql.gz",
+                "03_nodes.sql.gz",
+                "04_media.sql.gz",
+                "05_media_revisions.sql.gz"
+            ]
+        );
+    }
+
+    #[test]
+    fn json_lines() {
+        let dir = input_directory();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        write(
+            "media_revisions.csv",
+            "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n\
+             a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,foo\n",
+        );
+        write(
+            "files.csv",
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,0\n\
+             a:1,OBJ,OBJ.1,0,image/png,a.png,public://b.png,foo,,0\n",
+        );
+        write(
+            "users.jsonl",
+            r#"{"name":"foo","pass":"","mail":"","status":1,"timezone":"UTC","language":"en"}"#,
+        );
+        write(
+            "files.jsonl",
+            r#"{"pid":"a:1","dsid":"OBJ","version":"OBJ.0","created_date":0,"mime_type":"image/png","name":"a.png","path":"public://a.png","user":"foo","sha1":null,"size":0}
+               {"pid":"a:1","dsid":"OBJ","version":"OBJ.1","created_date":0,"mime_type":"image/png","name":"a.png","path":"public://b.png","user":"foo","sha1":"","size":"0"}"#,
+        );
+        let media = |version: &str, user: &str| {
+            format!(
+                r#"{{"pid":"a:1","dsid":"OBJ","version":"{}","bundle":"image","created_date":"0","file_size":"0","label":"","mime_type":"image/png","name":"a.png","user":"{}"}}"#,
+                version, user
+            )
+        };
+        write("media.jsonl", &media("OBJ.0", "admin"));
+        // Blank lines are ignored.
+        write(
+            "media_revisions.jsonl",
+            &format!("{}\n\n", media("OBJ.1", "foo")),
+        );
+        write(
+            "nodes.jsonl",
+            r#"{"pid":"a:1","created_date":"0","label":"A","weight":"","model":"","modified_date":"0","state":"Active","user":"foo","display_hint":"","parents":""}"#,
+        );
+        let mut config = MigrationConfig {
+            deterministic_uuids: true,
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let csv = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        let csv_ids = dump_id_maps_with_config(dir.path(), &config).unwrap();
+        config.input_format = InputFormat::JSONLines;
+        config.file_names = FileNames {
+            users: "users.jsonl".to_string(),
+            files: "files.jsonl".to_string(),
+            media: "media.jsonl".to_string(),
+            media_revisions: "media_revisions.jsonl".to_string(),
+            nodes: "nodes.jsonl".to_string(),
+        };
+        assert_eq!(validate_headers(dir.path(), &config), Ok(()));
+        // Including the row hashes, as the keys are in the same order as the columns.
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config).unwrap(),
+            csv
+        );
+        assert_eq!(
+            dump_id_maps_with_config(dir.path(), &config).unwrap(),
+            csv_ids
+        );
+        write(
+            "nodes.jsonl",
+            r#"{"pid":"a:1","created_date":"0","label":"A","weight":"","model":"","modified_date":"0","state":"Active","user":"foo","display_hint":"","parents":["a:2"]}"#,
+        );
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config)
+                .unwrap_err()
+                .to_string(),
+            "nodes.jsonl:1: objects and arrays are not supported as values"
+        );
+        config.continue_on_error = true;
+        let report = validate_with_config(dir.path(), &config).unwrap();
+        assert!(matches!(
+            report.skipped.as_slice(),
+            [Error::JSONRow { line: 1, .. }]
+        ));
+    }
+
+    // A zip archive of the given entries, deflated if the bool is true.
+    fn zip_archive(entries: &[(&str, Vec<u8>, bool)]) -> Vec<u8> {
+        let mut archive = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        for (name, contents, deflate) in entries {
+            let method = if *deflate {
+                zip::CompressionMethod::Deflated
+            } else {
+                zip::CompressionMethod::Stored
+            };
+            let options = zip::write::SimpleFileOptions::default().compression_method(method);
+            archive.start_file(*name, options).unwrap();
+            archive.write_all(contents).unwrap();
+        }
+        archive.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn zip_input() {
+        let dir = input_directory();
+        fs::write(
+            dir.path().join("media_revisions.csv"),
+            "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n",
+        )
+        .unwrap();
+        let config = MigrationConfig {
+            deterministic_uuids: true,
+            generated_at: Some(1_600_000_000),
+            ..MigrationConfig::default()
+        };
+        let expected = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        let read = |name: &str| fs::read(dir.path().join(name)).unwrap();
+        let archives = tempfile::tempdir().unwrap();
+        let archive = archives.path().join("export.zip");
+        // Entries may be in a directory and other files are ignored.
+        let mut entries = vec![
+            ("export/users.csv", read("users.csv"), true),
+            ("export/files.csv", read("files.csv"), false),
+            ("export/media.csv", read("media.csv"), true),
+            (
+                "export/media_revisions.csv",
+                read("media_revisions.csv"),
+                false,
+            ),
+            ("export/nodes.csv", read("nodes.csv"), true),
+            ("export/README.txt", b"Not an input file".to_vec(), false),
+        ];
+        fs::write(&archive, zip_archive(&entries)).unwrap();
+        assert!(is_archive(&archive));
+        assert!(!is_archive(dir.path()));
+        assert_eq!(valid_source_directory(&archive, &config), Ok(()));
+        assert_eq!(
+            generate_sql_string_with_config(&archive, &config).unwrap(),
+            expected
+        );
+        let output = tempfile::tempdir().unwrap();
+        generate_sql_with_config(&archive, output.path(), &config).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.path().join("migrate.sql")).unwrap(),
+            expected
+        );
+
+        entries.push(("nodes.csv", read("nodes.csv"), false));
+        fs::write(&archive, zip_archive(&entries)).unwrap();
+        assert_eq!(
+            generate_sql_string_with_config(&archive, &config)
+                .unwrap_err()
+                .to_string(),
+            "Invalid zip archive: 'nodes.csv' is in the archive more than once"
+        );
+
+        entries.pop();
+        // The first byte of users.csv, which is stored uncompressed.
+        entries[0].2 = false;
+        let mut corrupt = zip_archive(&entries);
+        let users = read("users.csv");
+        let start = corrupt
+            .windows(users.len())
+            .position(|window| window == users.as_slice())
+            .unwrap();
+        corrupt[start] ^= 1;
+        fs::write(&archive, corrupt).unwrap();
+        assert_eq!(
+            generate_sql_string_with_config(&archive, &config)
+                .unwrap_err()
+                .to_string(),
+            "Invalid zip archive: 'export/users.csv' is corrupt"
+        );
+
+        fs::write(&archive, "Not a zip archive").unwrap();
+        assert!(matches!(
+            generate_sql_string_with_config(&archive, &config),
+            Err(Error::InvalidArchive(_))
+        ));
+    }
+
+    #[test]
+    fn column_count() {
+        assert_eq!(count_values("(1,'a','b')", Dialect::MySQL), 3);
+        assert_eq!(
+            count_values(r"(1,'a,\',b',CONCAT('(',2),NULL)", Dialect::MySQL),
+            4
+        );
+        assert_eq!(count_values("(1,'a,'',b\\',NULL)", Dialect::SQLite), 3);
+        let config = MigrationConfig::default();
+        let table = |columns: &[&str]| Table {
+            name: "file_managed".to_string(),
+            columns: super::columns(columns),
+            values: Box::new(vec![Ok("(1,'a,b',2)".to_string())].into_iter()),
+        };
+        let mut sql = vec![];
+        assert_eq!(
+            table(&["fid", "filename", "filesize"])
+                .dump(&mut sql, &config)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            table(&["fid", "filename", "filesize", "status"])
+                .dump(&mut sql, &config)
+                .unwrap_err()
+                .to_string(),
+            "The rows of table 'file_managed' have 3 values but it has 4 columns"
+        );
+    }
+
+    #[test]
+    fn media_of() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(
+            dir.path().join("files.csv"),
+            "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+             a:1,OBJ,OBJ.0,0,image/png,a.png,public://a.png,foo,,0\n\
+             a:1,OBJ,OBJ.1,0,image/png,a.png,public://b.png,foo,,0\n\
+             a:1,PDF,PDF.0,0,application/pdf,a.pdf,public://a.pdf,foo,,0\n\
+             b:1,OBJ,OBJ.0,0,image/png,b.png,public://c.png,foo,,0\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("media.csv"),
+            format!(
+                "{}a:1,OBJ,OBJ.1,image,0,0,,image/png,a.png,admin\n\
+                 a:1,PDF,PDF.0,document,0,0,,application/pdf,a.pdf,admin\n\
+                 b:1,OBJ,OBJ.0,image,0,0,,image/png,b.png,admin\n",
+                media
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("media_revisions.csv"),
+            format!("{}a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,foo\n", media),
+        )
+        .unwrap();
+        let sql = generate_sql_string_with_config(dir.path(), &MigrationConfig::default()).unwrap();
+        // Both datastreams of a:1 belong to its node, b:1 has no node.
+        assert!(sql.contains(
+            "INSERT INTO `media__field_media_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_of_target_id) VALUES\n  \
+             (1,1,'image',0,'en',0,1),\n  \
+             (2,2,'document',0,'en',0,1);"
+        ));
+        assert!(sql.contains(
+            "INSERT INTO `media_revision__field_media_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_of_target_id) VALUES\n  \
+             (1,1,'image',0,'en',0,1),\n  \
+             (2,2,'document',0,'en',0,1),\n  \
+             (1,4,'image',0,'en',0,1);"
+        ));
+        // The nodes are written first so their ids are known.
+        assert!(sql.find("`node`").unwrap() < sql.find("`media`").unwrap());
+    }
+
+    #[test]
+    fn case_insensitive_users() {
+        let dir = input_directory();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        write(
+            "media_revisions.csv",
+            "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n",
+        );
+        write(
+            "users.csv",
+            "name,pass,mail,status,timezone,language\nFoo,,,1,UTC,en\n",
+        );
+        write(
+            "nodes.csv",
+            "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+             a:1,0,A,,,0,Active,FOO,,\n",
+        );
+        let mut config = MigrationConfig::default();
+        assert!(matches!(
+            generate_sql_string_with_config(dir.path(), &config),
+            Err(Error::UnknownUsers(_))
+        ));
+        config.case_insensitive_users = true;
+        config.reserved_users = vec![("Admin".to_string(), 1)].into_iter().collect();
+        // The node's FOO is users.csv's Foo and the media's admin the reserved Admin.
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        // The name is migrated as it is, only the source id is lowercased.
+        assert!(sql.contains("(2,'en','Foo',"));
+        assert!(sql.contains(&format!("('{}','foo',2,", source_ids_hash(&["foo"]))));
+        assert_eq!(
+            dump_id_maps_with_config(dir.path(), &config).unwrap()["uid"]
+                [source_ids_hash(&["foo"])],
+            2
+        );
+        write(
+            "users.csv",
+            "name,pass,mail,status,timezone,language\nFoo,,,1,UTC,en\nfoo,,,1,UTC,en\n",
+        );
+        assert!(matches!(
+            generate_sql_string_with_config(dir.path(), &config),
+            Err(Error::DuplicateSourceId { .. })
+        ));
+    }
+
+    #[test]
+    fn remote_media() {
+        let dir = input_directory();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        let media =
+            "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user,url\n";
+        write(
+            "media.csv",
+            &format!(
+                "{}a:1,OBJ,OBJ.0,image,0,0,,image/png,a.png,admin,\n\
+                 a:1,VIDEO,VIDEO.0,remote_video,0,0,,video/mp4,Video,admin,https://youtu.be/x\n",
+                media
+            ),
+        );
+        write("media_revisions.csv", media);
+        let config = MigrationConfig::default();
+        // There is no file of the video.
+        let report = validate_with_config(dir.path(), &config).unwrap();
+        assert!(report.unresolved.is_empty());
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        for table in &["media", "media_revision"] {
+            assert!(sql.contains(&format!(
+                "INSERT INTO `{}__field_media_oembed_video` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_oembed_video_value) VALUES\n  (2,2,'remote_video',0,'en',0,'https://youtu.be/x');",
+                table
+            )));
+        }
+        assert!(sql.contains(
+            "INSERT INTO `file_usage` (fid,module,type,id,count) VALUES\n  (1,'file','media',1,1);"
+        ));
+        write(
+            "media.csv",
+            &format!(
+                "{}a:1,VIDEO,VIDEO.0,remote_video,0,0,,video/mp4,Video,admin,\n",
+                media
+            ),
+        );
+        assert_eq!(
+            generate_sql_string_with_config(dir.path(), &config)
+                .unwrap_err()
+                .to_string(),
+            "Remote media (a:1, VIDEO, VIDEO.0) has no url"
+        );
+    }
+
+    #[test]
+    fn user_roles() {
+        let dir = input_directory();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        write(
+            "media_revisions.csv",
+            "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n",
+        );
+        write(
+            "users.csv",
+            "name,pass,mail,status,timezone,language,roles\n\
+             foo,,,1,UTC,en,editor; fedora'admin\n\
+             bar,,,1,UTC,en,\n",
+        );
+        let mut config = MigrationConfig::default();
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        // Without the delimiter the column is a single role.
+        assert!(sql.contains("(2,2,'user',0,'en',0,'editor; fedora\\'admin');"));
+        config.role_delimiter = ';';
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(sql.contains(
+            "INSERT INTO `user__roles` (entity_id,revision_id,bundle,deleted,langcode,delta,roles_target_id) VALUES\n  \
+             (2,2,'user',0,'en',0,'editor'),\n  \
+             (2,2,'user',0,'en',1,'fedora\\'admin');"
+        ));
+        write(
+            "users.csv",
+            "name,pass,mail,status,timezone,language,roles\nfoo,,,1,UTC,en,\n",
+        );
+        let sql = generate_sql_string_with_config(dir.path(), &config).unwrap();
+        assert!(!sql.contains("user__roles"));
+    }
+
+    #[test]
+    fn report_duplicates() {
+        let dir = input_directory();
+        let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+        fs::write(dir.path().join("media_revisions.csv"), media).unwrap();
+        fs::write(
+            dir.path().join("users.csv"),
+            "name,pass,mail,status,timezone,language\nfoo,,,1,UTC,en\nfoo,,,0,UTC,en\n",
+        )
+        .unwrap();
+        let mut config = MigrationConfig::default();
+        assert!(matches!(
+            generate_sql_string_with_config(dir.path(), &config),
+            Err(Error::DuplicateSourceId { .. })
+        ));
+        config.duplicates = Duplicates::Report;
+        let mut output = vec![];
+        let stats = generate_sql_to(dir.path(), &mut output, &config).unwrap();
+        assert_eq!(
+            stats
+                .skipped
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec!["users.csv:3: Duplicate source ids (foo) of an earlier row"]
+        );
+        // The first of the duplicates is migrated.
+        assert_eq!(stats.entities["user"], 1);
+        assert_eq!(stats.tables["users_field_data"], 1);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains(",0,0,1,NULL,NULL,1,'UTC')"));
     }
 }
@@ -0,0 +1,843 @@
+use crate::common::*;
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::path::Path;
+use tempfile::{tempfile, tempfile_in};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub(crate) struct MediaRow {
+    pid: String,
+    dsid: String,
+    version: String,
+    bundle: String,
+    created_date: String,
+    file_size: String,
+    label: String,
+    mime_type: String,
+    name: String,
+    user: String,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+impl SourceRow for MediaRow {
+    fn id() -> IdMaps {
+        IdMaps::MID
+    }
+
+    fn offset(offsets: &Offsets) -> usize {
+        offsets.media
+    }
+
+    fn file_name(names: &FileNames) -> &str {
+        &names.media
+    }
+
+    fn take(files: &mut ParsedFiles) -> Rows<Self> {
+        std::mem::take(&mut files.media)
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &[
+            "pid",
+            "dsid",
+            "version",
+            "bundle",
+            "created_date",
+            "file_size",
+            "label",
+            "mime_type",
+            "name",
+            "user",
+        ]
+    }
+
+    fn optional_headers() -> &'static [&'static str] {
+        &["url"]
+    }
+
+    fn complete(&mut self, config: &MigrationConfig) -> Result<()> {
+        config.normalize_user(&mut self.user);
+        complete_bundle(&mut self.bundle, &self.mime_type, &self.name, config)?;
+        check_url(self, config)
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![self.pid.as_str(), self.dsid.as_str()]
+    }
+
+    fn references(&self, config: &MigrationConfig) -> Vec<(IdMaps, Vec<&str>)> {
+        let mut references = vec![(IdMaps::UID, vec![self.user.as_str()])];
+        if !self.is_remote(config) {
+            references.push((IdMaps::FID, self.file_ids().to_vec()));
+        }
+        references
+    }
+}
+
+// Media and their revisions must agree on the bundle, so both derive it from the
+// mime type the same way, guessing it from the name as for files if need be.
+fn complete_bundle(
+    bundle: &mut String,
+    mime_type: &str,
+    name: &str,
+    config: &MigrationConfig,
+) -> Result<()> {
+    if bundle.is_empty() {
+        *bundle = config
+            .media_bundle(mime_type_or_guess(mime_type, name))?
+            .to_string();
+    }
+    Ok(())
+}
+
+// Media and media revision rows both reference the file of the revision, or
+// if their bundle is remote the url of their content.
+pub(crate) trait MediaFileRow {
+    fn bundle(&self) -> &str;
+    fn file_ids(&self) -> [&str; 3];
+    fn url(&self) -> Option<&str>;
+    // The mime type and name as given in the CSV file.
+    fn mime_type_and_name(&self) -> (&str, &str);
+
+    // Values made up for those the export left empty, to be warned about.
+    fn coercions(&self) -> Vec<String> {
+        let (mime_type, name) = self.mime_type_and_name();
+        mime_type_coercion(mime_type, name).into_iter().collect()
+    }
+
+    fn is_remote(&self, config: &MigrationConfig) -> bool {
+        config.remote_media_field(self.bundle()).is_some()
+    }
+}
+
+impl MediaFileRow for MediaRow {
+    fn bundle(&self) -> &str {
+        &self.bundle
+    }
+
+    fn file_ids(&self) -> [&str; 3] {
+        [&self.pid, &self.dsid, &self.version]
+    }
+
+    fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    fn mime_type_and_name(&self) -> (&str, &str) {
+        (&self.mime_type, &self.name)
+    }
+}
+
+// The url column is optional as only remote media need it, though
+// media_revisions.csv must have it if media.csv does as the two are merged.
+fn check_url(row: &impl MediaFileRow, config: &MigrationConfig) -> Result<()> {
+    let missing = row.url().is_none_or(|url| url.trim().is_empty());
+    if row.is_remote(config) && missing {
+        let [pid, dsid, version] = row.file_ids();
+        return Err(Error::MissingURL {
+            pid: pid.to_string(),
+            dsid: dsid.to_string(),
+            version: version.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// The field which references the file varies by bundle, so each field gets a
+// table containing only the media of the bundles which use it.
+pub(crate) fn media_file_tables<'a, T>(
+    map: &'a MigrateMap<T>,
+    config: &'a MigrationConfig,
+    revisions: bool,
+) -> Vec<Table<'a>>
+where
+    T: SourceRow + MediaFileRow,
+{
+    let mut fields = map
+        .map
+        .values()
+        .filter(|row| !row.is_remote(config))
+        .map(|row| config.media_file_field(row.bundle()))
+        .collect::<Vec<_>>();
+    fields.sort_unstable();
+    fields.dedup();
+    fields
+        .into_iter()
+        .map(|field| Table {
+            name: if revisions {
+                format!("media_revision__{}", field)
+            } else {
+                format!("media__{}", field)
+            },
+            columns: columns(&[
+                "entity_id",
+                "revision_id",
+                "bundle",
+                "deleted",
+                "langcode",
+                "delta",
+                &format!("{}_target_id", field),
+            ]),
+            values: Box::new(
+                map.rows(config)
+                    .filter(move |(_, (_, row))| {
+                        !row.is_remote(config) && config.media_file_field(row.bundle()) == field
+                    })
+                    .map(move |(index, (_, row))| {
+                        let [pid, dsid, version] = row.file_ids();
+                        // Only revisions need to look up the media they belong to.
+                        let mid = if revisions {
+                            map.mid(pid, dsid)?
+                        } else {
+                            index
+                        };
+                        Ok(format!(
+                            "({},{},{},0,{},0,{})",
+                            mid,
+                            index,
+                            config.dialect.quote(row.bundle()),
+                            config.dialect.quote(&config.langcode),
+                            map.fid(pid, dsid, version)?,
+                        ))
+                    }),
+            ),
+        })
+        .collect()
+}
+
+// Remote media, e.g. videos hosted elsewhere, have the url of their content
+// rather than a file, in the field of their bundle.
+pub(crate) fn remote_media_tables<'a, T>(
+    map: &'a MigrateMap<T>,
+    config: &'a MigrationConfig,
+    revisions: bool,
+) -> Vec<Table<'a>>
+where
+    T: SourceRow + MediaFileRow,
+{
+    let mut fields = map
+        .map
+        .values()
+        .filter_map(|row| config.remote_media_field(row.bundle()))
+        .collect::<Vec<_>>();
+    fields.sort_unstable();
+    fields.dedup();
+    fields
+        .into_iter()
+        .map(|field| Table {
+            name: if revisions {
+                format!("media_revision__{}", field)
+            } else {
+                format!("media__{}", field)
+            },
+            columns: columns(&[
+                "entity_id",
+                "revision_id",
+                "bundle",
+                "deleted",
+                "langcode",
+                "delta",
+                &format!("{}_value", field),
+            ]),
+            values: Box::new(
+                map.rows(config)
+                    .filter(move |(_, (_, row))| {
+                        config.remote_media_field(row.bundle()) == Some(field)
+                    })
+                    .map(move |(index, (_, row))| {
+                        let [pid, dsid, _] = row.file_ids();
+                        let mid = if revisions {
+                            map.mid(pid, dsid)?
+                        } else {
+                            index
+                        };
+                        Ok(format!(
+                            "({},{},{},0,{},0,{})",
+                            mid,
+                            index,
+                            config.dialect.quote(row.bundle()),
+                            config.dialect.quote(&config.langcode),
+                            config.dialect.quote(row.url().unwrap_or_default()),
+                        ))
+                    }),
+            ),
+        })
+        .collect()
+}
+
+// Islandora links each media to the node of the object it is a datastream of,
+// so an object with many datastreams has many media. Media of objects without
+// a node, e.g. one excluded by the limit, are not linked to any.
+pub(crate) fn media_of_tables<'a, T>(
+    map: &'a MigrateMap<T>,
+    config: &'a MigrationConfig,
+    revisions: bool,
+) -> Table<'a>
+where
+    T: SourceRow + MediaFileRow,
+{
+    Table {
+        name: if revisions {
+            "media_revision__field_media_of".to_string()
+        } else {
+            "media__field_media_of".to_string()
+        },
+        columns: columns(&[
+            "entity_id",
+            "revision_id",
+            "bundle",
+            "deleted",
+            "langcode",
+            "delta",
+            "field_media_of_target_id",
+        ]),
+        values: Box::new(map.rows(config).filter_map(move |(index, (_, row))| {
+            let [pid, dsid, _] = row.file_ids();
+            let nid = map.lookup(&IdMaps::NID, &[pid])?;
+            let mid = if revisions {
+                map.mid(pid, dsid)
+            } else {
+                Ok(index)
+            };
+            Some(mid.map(|mid| {
+                format!(
+                    "({},{},{},0,{},0,{})",
+                    mid,
+                    index,
+                    config.dialect.quote(row.bundle()),
+                    config.dialect.quote(&config.langcode),
+                    nid
+                )
+            }))
+        })),
+    }
+}
+
+pub(crate) type MigrateMediaMap = MigrateMap<MediaRow>;
+
+impl MigrateMediaMap {
+    // Media without a thumbnail are skipped. As with the file field, the
+    // revision table only refers to the latest revision, whose vid is the mid.
+    fn thumbnail_tables<'a>(
+        &'a self,
+        config: &'a MigrationConfig,
+        thumbnails: &'a ThumbnailMap,
+    ) -> Vec<Table<'a>> {
+        vec!["media", "media_revision"]
+            .into_iter()
+            .map(|entity| Table {
+                name: format!("{}__thumbnail", entity),
+                columns: columns(&[
+                    "entity_id",
+                    "revision_id",
+                    "bundle",
+                    "deleted",
+                    "langcode",
+                    "delta",
+                    "thumbnail_target_id",
+                ]),
+                values: Box::new(self.rows(config).filter_map(move |(index, (_, media))| {
+                    let (pid, dsid, version) =
+                        thumbnails.get(&(media.pid.clone(), media.dsid.clone()))?;
+                    Some(self.fid(pid, dsid, version).map(|fid| {
+                        format!(
+                            "({},{},{},0,{},0,{})",
+                            index,
+                            index,
+                            config.dialect.quote(&media.bundle),
+                            config.dialect.quote(&config.langcode),
+                            fid
+                        )
+                    }))
+                })),
+            })
+            .collect()
+    }
+}
+
+impl TableSerializer for MigrateMediaMap {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        let mut tables = vec![
+            Table {
+                name: "media".to_string(),
+                columns: columns(&["mid", "vid", "bundle", "uuid", "langcode"]),
+                values: self.values(config, move |(index, (hash, media))| {
+                    Ok(format!(
+                        "({},{},{},'{}',{})",
+                        index,
+                        index,
+                        config.dialect.quote(&media.bundle),
+                        uuid("media", hash, config),
+                        config.dialect.quote(&config.langcode),
+                    ))
+                }),
+            },
+            Table {
+                name: "media_field_data".to_string(),
+                columns: columns(&[
+                    "mid",
+                    "vid",
+                    "bundle",
+                    "langcode",
+                    "status",
+                    "uid",
+                    "name",
+                    "created",
+                    "changed",
+                    "default_langcode",
+                ]),
+                values: self.values(config, move |(index, (_, media))| {
+                    Ok(format!(
+                        "({},{},{},{},1,{},{},{},{}, 1)",
+                        index,
+                        index,
+                        config.dialect.quote(&media.bundle),
+                        config.dialect.quote(&config.langcode),
+                        self.uid(&media.user, config)?,
+                        config.dialect.quote(&media.name),
+                        config.timestamp(&media.pid, &media.created_date)?,
+                        config.timestamp(&media.pid, &media.created_date)?,
+                    ))
+                }),
+            },
+        ];
+        tables.extend(media_file_tables(self, config, false));
+        tables.extend(remote_media_tables(self, config, false));
+        tables.push(media_of_tables(self, config, false));
+        if let Some(thumbnails) = &config.thumbnails {
+            tables.extend(self.thumbnail_tables(config, thumbnails));
+        }
+        if config.emit_map_tables {
+            tables.push(Table {
+                name: config.map_table("media"),
+                columns: columns(&[
+                    "source_ids_hash",
+                    "sourceid1",
+                    "sourceid2",
+                    "destid1",
+                    "source_row_status",
+                    "last_imported",
+                    "hash",
+                ]),
+                values: self.migrate_map_values(config),
+            });
+        }
+        tables
+    }
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub(crate) struct MediaRevisionRow {
+    pid: String,
+    dsid: String,
+    version: String,
+    bundle: String,
+    created_date: String,
+    file_size: String,
+    label: String,
+    mime_type: String,
+    name: String,
+    user: String,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+impl MediaFileRow for MediaRevisionRow {
+    fn bundle(&self) -> &str {
+        &self.bundle
+    }
+
+    fn file_ids(&self) -> [&str; 3] {
+        [&self.pid, &self.dsid, &self.version]
+    }
+
+    fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    fn mime_type_and_name(&self) -> (&str, &str) {
+        (&self.mime_type, &self.name)
+    }
+}
+
+impl SourceRow for MediaRevisionRow {
+    fn id() -> IdMaps {
+        IdMaps::VID
+    }
+
+    fn offset(offsets: &Offsets) -> usize {
+        offsets.media
+    }
+
+    // Line numbers reported for this file are relative to the merged input
+    // described below, rather than media_revisions.csv itself.
+    fn file_name(names: &FileNames) -> &str {
+        &names.media_revisions
+    }
+
+    fn take(files: &mut ParsedFiles) -> Rows<Self> {
+        std::mem::take(&mut files.media_revisions)
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &[
+            "pid",
+            "dsid",
+            "version",
+            "bundle",
+            "created_date",
+            "file_size",
+            "label",
+            "mime_type",
+            "name",
+            "user",
+        ]
+    }
+
+    fn optional_headers() -> &'static [&'static str] {
+        &["url"]
+    }
+
+    fn csv(path: &Path, config: &MigrationConfig) -> Result<Box<dyn Read>> {
+        // Media rows are also part of media_revisions so we merge the two files
+        // with the media.csv being first to preserve the correct order for mid
+        // and vid. Additionally we need to remove the additional header in
+        // media_revisions.csv. The merged file can be as large as both, so it
+        // can be put somewhere with more space than the temporary directory.
+        let names = &config.file_names;
+        let temp = match &config.temp_directory {
+            Some(directory) => tempfile_in(directory)?,
+            None => tempfile()?,
+        };
+        let mut csv = BufWriter::new(temp);
+        let media = BufReader::new(open(&path.join(&names.media))?);
+        let media_revisions = BufReader::new(open(&path.join(&names.media_revisions))?);
+        let header = match config.input_format {
+            InputFormat::CSV => 1,
+            InputFormat::JSONLines => 0,
+        };
+        copy_lines(media, &mut csv, 0)?;
+        copy_lines(media_revisions, &mut csv, header)?;
+        let mut csv = csv.into_inner().map_err(|error| error.into_error())?;
+        csv.seek(SeekFrom::Start(0))?;
+        Ok(Box::new(csv))
+    }
+
+    fn complete(&mut self, config: &MigrationConfig) -> Result<()> {
+        config.normalize_user(&mut self.user);
+        complete_bundle(&mut self.bundle, &self.mime_type, &self.name, config)?;
+        check_url(self, config)
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![self.pid.as_str(), self.dsid.as_str(), self.version.as_str()]
+    }
+
+    fn version(&self) -> Option<([&str; 2], &str)> {
+        Some(([&self.pid, &self.dsid], &self.version))
+    }
+}
+
+impl From<&MediaRow> for MediaRevisionRow {
+    fn from(row: &MediaRow) -> Self {
+        MediaRevisionRow {
+            pid: row.pid.clone(),
+            dsid: row.dsid.clone(),
+            version: row.version.clone(),
+            bundle: row.bundle.clone(),
+            created_date: row.created_date.clone(),
+            file_size: row.file_size.clone(),
+            label: row.label.clone(),
+            mime_type: row.mime_type.clone(),
+            name: row.name.clone(),
+            user: row.user.clone(),
+            url: row.url.clone(),
+        }
+    }
+}
+
+impl From<&MediaRevisionMigrateMapRow> for MediaRevisionRow {
+    fn from(row: &MediaRevisionMigrateMapRow) -> Self {
+        MediaRevisionRow {
+            pid: row.pid.clone(),
+            dsid: row.dsid.clone(),
+            version: row.version.clone(),
+            bundle: row.bundle.clone(),
+            created_date: row.created_date.clone(),
+            file_size: row.file_size.clone(),
+            label: row.label.clone(),
+            mime_type: row.mime_type.clone(),
+            name: row.name.clone(),
+            user: row.user.clone(),
+            url: row.url.clone(),
+        }
+    }
+}
+
+impl MediaRevisionRow {
+    // The same rows as parsing the merged csv() but built from media.csv and
+    // media_revisions.csv as already parsed, so neither is read twice. The row
+    // hashes do not depend on the source ids so they are kept as is.
+    pub(crate) fn merge(
+        (media, media_hashes): &Rows<MediaRow>,
+        (revisions, revision_hashes): &Rows<MediaRevisionMigrateMapRow>,
+        config: &MigrationConfig,
+        skipped: &mut Vec<Error>,
+    ) -> Result<Rows<Self>> {
+        let mut map = IndexMap::with_capacity(media.len() + revisions.len());
+        let mut hashes = IndexMap::with_capacity(media.len() + revisions.len());
+        for (media_key, row) in media {
+            let row = Self::from(row);
+            let key = row.source_ids_hash();
+            hashes.insert(key.clone(), media_hashes[media_key].clone());
+            map.insert(key, row);
+        }
+        for (key, row) in revisions {
+            if map.contains_key(key) {
+                // Rows are not parsed with their position, so only the source
+                // ids identify the duplicate.
+                let duplicate = Error::DuplicateMediaRevision {
+                    file: config.file_names.media_revisions.clone(),
+                    ids: row.source_ids().iter().map(|s| s.to_string()).collect(),
+                };
+                match config.duplicates {
+                    Duplicates::Error => return Err(duplicate),
+                    Duplicates::Report => {
+                        skipped.push(duplicate);
+                        continue;
+                    }
+                }
+            }
+            hashes.insert(key.clone(), revision_hashes[key].clone());
+            map.insert(key.clone(), Self::from(row));
+        }
+        Ok((map, hashes))
+    }
+}
+
+pub(crate) type MigrateMediaRevisionMapExcludingMigration = MigrateMap<MediaRevisionRow>;
+
+impl TableSerializer for MigrateMediaRevisionMapExcludingMigration {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        let mut tables = vec![
+            Table {
+                name: "media_revision".to_string(),
+                columns: {
+                    let mut columns = columns(&[
+                        "mid",
+                        "vid",
+                        "langcode",
+                        "revision_user",
+                        "revision_created",
+                        "revision_default",
+                    ]);
+                    if config.revision_log_message.is_some() {
+                        columns.push("revision_log_message".to_string());
+                    }
+                    columns
+                },
+                values: self.values(config, move |(index, (_, media_revision))| {
+                    let log_message = match &config.revision_log_message {
+                        Some(template) => format!(
+                            ",{}",
+                            config
+                                .dialect
+                                .quote(&template.replace("{version}", &media_revision.version))
+                        ),
+                        None => String::new(),
+                    };
+                    Ok(format!(
+                        "({},{},{},{},{},1{})",
+                        self.mid(&media_revision.pid, &media_revision.dsid)?,
+                        index,
+                        config.dialect.quote(&config.langcode),
+                        self.uid(&media_revision.user, config)?,
+                        config.timestamp(&media_revision.pid, &media_revision.created_date)?,
+                        log_message
+                    ))
+                }),
+            },
+            Table {
+                name: "media_field_revision".to_string(),
+                columns: columns(&[
+                    "mid",
+                    "vid",
+                    "langcode",
+                    "status",
+                    "uid",
+                    "name",
+                    "created",
+                    "changed",
+                    "default_langcode",
+                ]),
+                values: self.values(config, move |(index, (_, media))| {
+                    Ok(format!(
+                        "({},{},{},1,{},{},{},{}, 1)",
+                        self.mid(&media.pid, &media.dsid)?,
+                        index,
+                        config.dialect.quote(&config.langcode),
+                        self.uid(&media.user, config)?,
+                        config.dialect.quote(&media.name),
+                        config.timestamp(&media.pid, &media.created_date)?,
+                        config.timestamp(&media.pid, &media.created_date)?,
+                    ))
+                }),
+            },
+            // Every revision refers to a different file, all of which must be
+            // recorded as in use by the media otherwise they will be deleted.
+            // Remote media have no file.
+            Table {
+                name: "file_usage".to_string(),
+                columns: columns(&["fid", "module", "type", "id", "count"]),
+                values: Box::new(
+                    self.rows(config)
+                        .filter(move |(_, (_, media))| !media.is_remote(config))
+                        .map(move |(_, (_, media))| {
+                            Ok(format!(
+                                "({},'file','media',{},1)",
+                                self.fid(&media.pid, &media.dsid, &media.version)?,
+                                self.mid(&media.pid, &media.dsid)?,
+                            ))
+                        }),
+                ),
+            },
+        ];
+        tables.extend(media_file_tables(self, config, true));
+        tables.extend(remote_media_tables(self, config, true));
+        tables.push(media_of_tables(self, config, true));
+        tables
+    }
+}
+
+// Need a separate structure for revision mapping since the migration is meant
+// to only handle those revisions which are not the latest.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub(crate) struct MediaRevisionMigrateMapRow {
+    pid: String,
+    dsid: String,
+    version: String,
+    bundle: String,
+    created_date: String,
+    file_size: String,
+    label: String,
+    mime_type: String,
+    name: String,
+    user: String,
+    #[serde(default)]
+    url: Option<String>,
+}
+
+impl MediaFileRow for MediaRevisionMigrateMapRow {
+    fn bundle(&self) -> &str {
+        &self.bundle
+    }
+
+    fn file_ids(&self) -> [&str; 3] {
+        [&self.pid, &self.dsid, &self.version]
+    }
+
+    fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    fn mime_type_and_name(&self) -> (&str, &str) {
+        (&self.mime_type, &self.name)
+    }
+}
+
+impl SourceRow for MediaRevisionMigrateMapRow {
+    fn id() -> IdMaps {
+        IdMaps::VID
+    }
+
+    fn offset(offsets: &Offsets) -> usize {
+        offsets.media
+    }
+
+    fn file_name(names: &FileNames) -> &str {
+        &names.media_revisions
+    }
+
+    fn take(files: &mut ParsedFiles) -> Rows<Self> {
+        std::mem::take(&mut files.media_revisions_migrate_map)
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &[
+            "pid",
+            "dsid",
+            "version",
+            "bundle",
+            "created_date",
+            "file_size",
+            "label",
+            "mime_type",
+            "name",
+            "user",
+        ]
+    }
+
+    fn optional_headers() -> &'static [&'static str] {
+        &["url"]
+    }
+
+    fn complete(&mut self, config: &MigrationConfig) -> Result<()> {
+        config.normalize_user(&mut self.user);
+        complete_bundle(&mut self.bundle, &self.mime_type, &self.name, config)?;
+        check_url(self, config)
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![self.pid.as_str(), self.dsid.as_str(), self.version.as_str()]
+    }
+
+    fn references(&self, config: &MigrationConfig) -> Vec<(IdMaps, Vec<&str>)> {
+        let mut references = vec![
+            (IdMaps::UID, vec![self.user.as_str()]),
+            (IdMaps::MID, vec![self.pid.as_str(), self.dsid.as_str()]),
+        ];
+        if !self.is_remote(config) {
+            references.push((IdMaps::FID, self.source_ids()));
+        }
+        references
+    }
+
+    // The latest versions are in media.csv.
+    fn previous_version() -> bool {
+        true
+    }
+
+    // Numbered after the media by MediaRevisionRow.
+    fn ids_assigned_elsewhere() -> bool {
+        true
+    }
+}
+
+pub(crate) type MigrateMediaRevisionMapMigrationOnly = MigrateMap<MediaRevisionMigrateMapRow>;
+
+impl TableSerializer for MigrateMediaRevisionMapMigrationOnly {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        if !config.emit_map_tables {
+            return vec![];
+        }
+        vec![Table {
+            name: config.map_table("media_revisions"),
+            columns: columns(&[
+                "source_ids_hash",
+                "sourceid1",
+                "sourceid2",
+                "sourceid3",
+                "destid1",
+                "source_row_status",
+                "last_imported",
+                "hash",
+            ]),
+            values: self.migrate_map_values(config),
+        }]
+    }
+}
@@ -0,0 +1,524 @@
+use crate::common::*;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub(crate) struct NodeRow {
+    pid: String,
+    created_date: String,
+    label: String,
+    weight: String,
+    model: String,
+    modified_date: String,
+    state: String,
+    user: String,
+    display_hint: String,
+    parents: String,
+    #[serde(default)]
+    promote: Option<String>,
+    #[serde(default)]
+    sticky: Option<String>,
+}
+
+impl SourceRow for NodeRow {
+    fn id() -> IdMaps {
+        IdMaps::NID
+    }
+
+    fn offset(offsets: &Offsets) -> usize {
+        offsets.nodes
+    }
+
+    fn file_name(names: &FileNames) -> &str {
+        &names.nodes
+    }
+
+    fn take(files: &mut ParsedFiles) -> Rows<Self> {
+        std::mem::take(&mut files.nodes)
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &[
+            "pid",
+            "created_date",
+            "label",
+            "weight",
+            "model",
+            "modified_date",
+            "state",
+            "user",
+            "display_hint",
+            "parents",
+        ]
+    }
+
+    fn optional_headers() -> &'static [&'static str] {
+        &["promote", "sticky"]
+    }
+
+    fn complete(&mut self, config: &MigrationConfig) -> Result<()> {
+        config.normalize_user(&mut self.user);
+        Ok(())
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![self.pid.as_str()]
+    }
+
+    fn signed_integers(&self) -> Vec<(&'static str, &str)> {
+        vec![("weight", self.weight.as_str())]
+    }
+
+    fn references(&self, _config: &MigrationConfig) -> Vec<(IdMaps, Vec<&str>)> {
+        let mut references = vec![(IdMaps::UID, vec![self.user.as_str()])];
+        references.extend(self.parents().map(|parent| (IdMaps::NID, vec![parent])));
+        references
+    }
+}
+
+impl NodeRow {
+    // Objects without a weight come first.
+    pub(crate) fn weight(&self) -> Result<i64> {
+        if self.weight.trim().is_empty() {
+            return Ok(0);
+        }
+        self.weight.parse().map_err(|_| Error::InvalidWeight {
+            pid: self.pid.clone(),
+            weight: self.weight.clone(),
+        })
+    }
+
+    // Nodes without a value, or from files without the column, get the default.
+    fn flag(&self, column: &str, value: &Option<String>, default: u8) -> Result<u8> {
+        match value.as_deref().unwrap_or_default().to_lowercase().as_str() {
+            "" => Ok(default),
+            "1" | "true" | "yes" => Ok(1),
+            "0" | "false" | "no" => Ok(0),
+            _ => Err(Error::InvalidFlag {
+                pid: self.pid.clone(),
+                column: column.to_string(),
+                value: value.clone().unwrap_or_default(),
+            }),
+        }
+    }
+
+    // Promoted to the front page unless the promote column says otherwise.
+    pub(crate) fn promote(&self) -> Result<u8> {
+        self.flag("promote", &self.promote, 1)
+    }
+
+    pub(crate) fn sticky(&self) -> Result<u8> {
+        self.flag("sticky", &self.sticky, 0)
+    }
+
+    // Published (1) if the object's state is one of the published states.
+    pub(crate) fn status(&self, config: &MigrationConfig) -> u8 {
+        config.published_states.contains(&self.state) as u8
+    }
+
+    // The moderation state the object's state is mapped to.
+    pub(crate) fn moderation_state<'a>(&self, config: &'a MigrationConfig) -> Result<&'a str> {
+        config
+            .moderation_states
+            .get(&self.state)
+            .map(|state| state.as_str())
+            .ok_or_else(|| Error::UnknownState(self.state.clone()))
+    }
+
+    // The template with the placeholders replaced by the pid.
+    pub(crate) fn alias(&self, template: &str) -> String {
+        let slug = self
+            .pid
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+                _ => '-',
+            })
+            .collect::<String>();
+        template
+            .replace(PATH_ALIAS_PLACEHOLDERS[0], &self.pid)
+            .replace(PATH_ALIAS_PLACEHOLDERS[1], &slug)
+    }
+
+    // Pids of the objects this object is a member of.
+    pub(crate) fn parents(&self) -> impl Iterator<Item = &str> {
+        self.parents.split('|').filter(|parent| !parent.is_empty())
+    }
+}
+
+pub(crate) type MigrateNodeMap = MigrateMap<NodeRow>;
+
+impl MigrateNodeMap {
+    // Nodes can only be members of other nodes, which are all in this map.
+    pub(crate) fn nid(&self, pid: &str, config: &MigrationConfig) -> Result<usize> {
+        self.map
+            .get_index_of(&source_ids_hash(&[pid]))
+            .map(|index| NodeRow::offset(&config.offsets) + index)
+            .ok_or_else(|| Error::UnknownNode(pid.to_string()))
+    }
+
+    // Only a single revision of each node is migrated so the field's revision
+    // table is the same as its data table. The given function returns the
+    // properties of each of the field's values (deltas) for the given node.
+    pub(crate) fn field_tables<'a, F>(
+        &'a self,
+        config: &'a MigrationConfig,
+        field: &str,
+        properties: &[&str],
+        values: F,
+    ) -> Vec<Table<'a>>
+    where
+        F: Fn(&'a NodeRow) -> Result<Vec<String>> + Copy + 'a,
+    {
+        let mut columns = columns(&[
+            "entity_id",
+            "revision_id",
+            "bundle",
+            "deleted",
+            "langcode",
+            "delta",
+        ]);
+        columns.extend(
+            properties
+                .iter()
+                .map(|property| format!("{}_{}", field, property)),
+        );
+        vec!["node", "node_revision"]
+            .into_iter()
+            .map(|entity| Table {
+                name: format!("{}__{}", entity, field),
+                columns: columns.clone(),
+                values: Box::new(self.rows(config).flat_map(move |(index, (_, node))| {
+                    match values(node) {
+                        Ok(values) => values
+                            .into_iter()
+                            .enumerate()
+                            .map(|(delta, value)| {
+                                Ok(format!(
+                                    "({},{},{},0,{},{},{})",
+                                    index,
+                                    index,
+                                    config.dialect.quote(&config.node_type),
+                                    config.dialect.quote(&config.langcode),
+                                    delta,
+                                    value
+                                ))
+                            })
+                            .collect::<Vec<_>>(),
+                        Err(error) => vec![Err(error)],
+                    }
+                })),
+            })
+            .collect()
+    }
+
+    // One value per parent, as objects can belong to multiple collections.
+    pub(crate) fn member_of(
+        &self,
+        node: &NodeRow,
+        config: &MigrationConfig,
+    ) -> Result<Vec<String>> {
+        node.parents()
+            .map(|parent| Ok(self.nid(parent, config)?.to_string()))
+            .collect()
+    }
+
+    // A single revision of a content moderation state entity for every node, so
+    // it is shown in the mapped workflow state.
+    pub(crate) fn moderation_tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        let mut tables = vec![
+            Table {
+                name: "content_moderation_state".to_string(),
+                columns: columns(&["id", "revision_id", "uuid", "langcode"]),
+                values: self.values(config, move |(index, (hash, _))| {
+                    Ok(format!(
+                        "({},{},'{}',{})",
+                        config.offsets.moderation_state(index),
+                        config.offsets.moderation_state(index),
+                        uuid("content_moderation_state", hash, config),
+                        config.dialect.quote(&config.langcode),
+                    ))
+                }),
+            },
+            Table {
+                name: "content_moderation_state_revision".to_string(),
+                columns: columns(&["id", "revision_id", "langcode", "revision_default"]),
+                values: self.values(config, move |(index, _)| {
+                    Ok(format!(
+                        "({},{},{},1)",
+                        config.offsets.moderation_state(index),
+                        config.offsets.moderation_state(index),
+                        config.dialect.quote(&config.langcode),
+                    ))
+                }),
+            },
+        ];
+        tables.extend(
+            vec![
+                "content_moderation_state_field_data",
+                "content_moderation_state_field_revision",
+            ]
+            .into_iter()
+            .map(|name| Table {
+                name: name.to_string(),
+                columns: columns(&[
+                    "id",
+                    "revision_id",
+                    "langcode",
+                    "uid",
+                    "workflow",
+                    "moderation_state",
+                    "content_entity_type_id",
+                    "content_entity_id",
+                    "content_entity_revision_id",
+                    "default_langcode",
+                    "revision_translation_affected",
+                ]),
+                values: self.values(config, move |(index, (_, node))| {
+                    Ok(format!(
+                        "({},{},{},{},{},{},'node',{},{},1,1)",
+                        config.offsets.moderation_state(index),
+                        config.offsets.moderation_state(index),
+                        config.dialect.quote(&config.langcode),
+                        self.uid(&node.user, config)?,
+                        config.dialect.quote(&config.moderation_workflow),
+                        config.dialect.quote(node.moderation_state(config)?),
+                        index,
+                        index,
+                    ))
+                }),
+            }),
+        );
+        tables
+    }
+
+    // An alias of /node/NID for every node.
+    pub(crate) fn path_alias_tables<'a>(
+        &'a self,
+        config: &'a MigrationConfig,
+        template: &'a str,
+    ) -> Vec<Table<'a>> {
+        vec![
+            Table {
+                name: "path_alias".to_string(),
+                columns: columns(&[
+                    "id",
+                    "revision_id",
+                    "uuid",
+                    "langcode",
+                    "path",
+                    "alias",
+                    "status",
+                ]),
+                values: self.values(config, move |(index, (hash, node))| {
+                    Ok(format!(
+                        "({},{},'{}',{},{},{},1)",
+                        config.offsets.path_alias(index),
+                        config.offsets.path_alias(index),
+                        uuid("path_alias", hash, config),
+                        config.dialect.quote(&config.langcode),
+                        config.dialect.quote(&format!("/node/{}", index)),
+                        config.dialect.quote(&node.alias(template)),
+                    ))
+                }),
+            },
+            Table {
+                name: "path_alias_revision".to_string(),
+                columns: columns(&[
+                    "id",
+                    "revision_id",
+                    "langcode",
+                    "path",
+                    "alias",
+                    "status",
+                    "revision_default",
+                ]),
+                values: self.values(config, move |(index, (_, node))| {
+                    Ok(format!(
+                        "({},{},{},{},{},1,1)",
+                        config.offsets.path_alias(index),
+                        config.offsets.path_alias(index),
+                        config.dialect.quote(&config.langcode),
+                        config.dialect.quote(&format!("/node/{}", index)),
+                        config.dialect.quote(&node.alias(template)),
+                    ))
+                }),
+            },
+        ]
+    }
+
+    // Objects without a model have no value.
+    pub(crate) fn model(node: &NodeRow, terms: &IndexMap<String, usize>) -> Result<Vec<String>> {
+        if node.model.is_empty() {
+            return Ok(vec![]);
+        }
+        terms
+            .get(&node.model)
+            .map(|tid| vec![tid.to_string()])
+            .ok_or_else(|| Error::UnknownModel(node.model.clone()))
+    }
+}
+
+impl TableSerializer for MigrateNodeMap {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        let mut tables = vec![
+            Table {
+                name: "node".to_string(),
+                columns: columns(&["nid", "vid", "type", "uuid", "langcode"]),
+                values: self.values(config, move |(index, (hash, _))| {
+                    Ok(format!(
+                        "({},{},{},'{}',{})",
+                        index,
+                        index,
+                        config.dialect.quote(&config.node_type),
+                        uuid("node", hash, config),
+                        config.dialect.quote(&config.langcode),
+                    ))
+                }),
+            },
+            Table {
+                name: "node_revision".to_string(),
+                columns: {
+                    let mut columns = columns(&[
+                        "nid",
+                        "vid",
+                        "langcode",
+                        "revision_uid",
+                        "revision_timestamp",
+                        "revision_default",
+                    ]);
+                    if config.node_revision_log.is_some() {
+                        columns.push("revision_log".to_string());
+                    }
+                    columns
+                },
+                values: self.values(config, move |(index, (_, node))| {
+                    let log = match &config.node_revision_log {
+                        Some(template) => format!(
+                            ",{}",
+                            config.dialect.quote(&template.replace("{pid}", &node.pid))
+                        ),
+                        None => String::new(),
+                    };
+                    Ok(format!(
+                        "({},{},{},{},{},1{})",
+                        index,
+                        index,
+                        config.dialect.quote(&config.langcode),
+                        self.uid(&node.user, config)?,
+                        config.timestamp(&node.pid, &node.modified_date)?,
+                        log
+                    ))
+                }),
+            },
+            Table {
+                name: "node_field_data".to_string(),
+                columns: columns(&[
+                    "nid",
+                    "vid",
+                    "type",
+                    "langcode",
+                    "status",
+                    "uid",
+                    "title",
+                    "created",
+                    "changed",
+                    "promote",
+                    "sticky",
+                    "default_langcode",
+                ]),
+                values: self.values(config, move |(index, (_, node))| {
+                    Ok(format!(
+                        "({},{},{},{},{},{},{},{},{},{},{},1)",
+                        index,
+                        index,
+                        config.dialect.quote(&config.node_type),
+                        config.dialect.quote(&config.langcode),
+                        node.status(config),
+                        self.uid(&node.user, config)?,
+                        config.dialect.quote(&node.label),
+                        config.timestamp(&node.pid, &node.created_date)?,
+                        config.timestamp(&node.pid, &node.modified_date)?,
+                        node.promote()?,
+                        node.sticky()?,
+                    ))
+                }),
+            },
+            Table {
+                name: "node_field_revision".to_string(),
+                columns: columns(&[
+                    "nid",
+                    "vid",
+                    "langcode",
+                    "status",
+                    "uid",
+                    "title",
+                    "created",
+                    "changed",
+                    "promote",
+                    "sticky",
+                    "default_langcode",
+                ]),
+                values: self.values(config, move |(index, (_, node))| {
+                    Ok(format!(
+                        "({},{},{},{},{},{},{},{},{},{},1)",
+                        index,
+                        index,
+                        config.dialect.quote(&config.langcode),
+                        node.status(config),
+                        self.uid(&node.user, config)?,
+                        config.dialect.quote(&node.label),
+                        config.timestamp(&node.pid, &node.created_date)?,
+                        config.timestamp(&node.pid, &node.modified_date)?,
+                        node.promote()?,
+                        node.sticky()?,
+                    ))
+                }),
+            },
+        ];
+        tables.extend(
+            self.field_tables(config, "field_member_of", &["target_id"], move |node| {
+                self.member_of(node, config)
+            }),
+        );
+        tables.extend(
+            self.field_tables(config, &config.pid_field, &["value"], move |node| {
+                Ok(vec![config.dialect.quote(&node.pid)])
+            }),
+        );
+        tables.extend(
+            self.field_tables(config, &config.weight_field, &["value"], |node| {
+                Ok(vec![node.weight()?.to_string()])
+            }),
+        );
+        if let Some(terms) = &config.model_terms {
+            tables.extend(
+                self.field_tables(config, "field_model", &["target_id"], move |node| {
+                    Self::model(node, terms)
+                }),
+            );
+        }
+        if !config.moderation_states.is_empty() {
+            tables.extend(self.moderation_tables(config));
+        }
+        if let Some(template) = &config.path_alias {
+            tables.extend(self.path_alias_tables(config, template));
+        }
+        if config.emit_map_tables {
+            tables.push(Table {
+                name: config.map_table("nodes"),
+                columns: columns(&[
+                    "source_ids_hash",
+                    "sourceid1",
+                    "destid1",
+                    "source_row_status",
+                    "last_imported",
+                    "hash",
+                ]),
+                values: self.migrate_map_values(config),
+            });
+        }
+        tables
+    }
+}
@@ -0,0 +1,128 @@
+// Adds an entity to the migration from outside of the crate through the public
+// SourceRow and EntityTables traits, as a site with its own entity types would.
+use serde::Deserialize;
+use sql::{
+    columns, dump_entity, Duplicates, EntityTables, FileNames, IdMaps, MigrateMap, MigrationConfig,
+    Offsets, SourceRow, Table,
+};
+use std::fs;
+use std::path::Path;
+
+#[derive(Deserialize)]
+struct PolicyRow {
+    pid: String,
+    policy: String,
+}
+
+impl SourceRow for PolicyRow {
+    fn id() -> IdMaps {
+        IdMaps::Custom {
+            entity: "collection policy",
+            column: "id",
+        }
+    }
+
+    fn offset(_offsets: &Offsets) -> usize {
+        1
+    }
+
+    fn file_name(_names: &FileNames) -> &str {
+        "policies.csv"
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &["pid", "policy"]
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![&self.pid]
+    }
+}
+
+impl EntityTables for PolicyRow {
+    fn tables<'a>(map: &'a MigrateMap<Self>, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        vec![Table {
+            name: "node__field_collection_policy".to_string(),
+            columns: columns(&["entity_id", "delta", "field_collection_policy_value"]),
+            // The node was written before, so its nid is known.
+            values: map.values(config, move |(index, (_, row))| {
+                let nid = map.lookup(&IdMaps::NID, &[&row.pid]).unwrap_or_default();
+                Ok(format!(
+                    "({},{},{})",
+                    nid,
+                    index - 1,
+                    config.dialect.quote(&row.policy)
+                ))
+            }),
+        }]
+    }
+}
+
+fn input_directory() -> tempfile::TempDir {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/quotes");
+    let dir = tempfile::tempdir().unwrap();
+    for name in &[
+        "users.csv",
+        "files.csv",
+        "media.csv",
+        "media_revisions.csv",
+        "nodes.csv",
+    ] {
+        fs::copy(fixture.join(name), dir.path().join(name)).unwrap();
+    }
+    fs::write(dir.path().join("policies.csv"), "pid,policy\ntest:1,open\n").unwrap();
+    dir
+}
+
+#[test]
+fn custom_entities() {
+    let dir = input_directory();
+    let mut config = MigrationConfig {
+        custom_entities: vec![("policies".to_string(), dump_entity::<PolicyRow>)],
+        ..MigrationConfig::default()
+    };
+    let sql = sql::generate_sql_string_with_config(dir.path(), &config).unwrap();
+    let policies = sql
+        .find("INSERT INTO `node__field_collection_policy`")
+        .unwrap();
+    assert!(policies > sql.find("INSERT INTO `node`").unwrap());
+    // test:1 is the second node.
+    assert!(sql[policies..].contains("(2,0,'open')"));
+
+    let output = tempfile::tempdir().unwrap();
+    config.split_output = true;
+    config.analyze_tables = true;
+    let stats = sql::generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+    assert_eq!(stats.entities["collection policy"], 1);
+    let policies = fs::read_to_string(output.path().join("06_policies.sql")).unwrap();
+    assert!(policies.contains("(2,0,'open')"));
+    let analyze = fs::read_to_string(output.path().join("07_analyze.sql")).unwrap();
+    assert!(analyze.contains("node__field_collection_policy"));
+}
+
+#[test]
+fn skipped_rows() {
+    let dir = input_directory();
+    fs::write(
+        dir.path().join("policies.csv"),
+        "pid,policy\ntest:1,open\ntest:1,closed\n",
+    )
+    .unwrap();
+    let mut config = MigrationConfig {
+        custom_entities: vec![("policies".to_string(), dump_entity::<PolicyRow>)],
+        ..MigrationConfig::default()
+    };
+    assert!(sql::generate_sql_string_with_config(dir.path(), &config).is_err());
+    config.duplicates = Duplicates::Report;
+    let output = tempfile::tempdir().unwrap();
+    let stats = sql::generate_sql_with_config(dir.path(), output.path(), &config).unwrap();
+    assert_eq!(stats.entities["collection policy"], 1);
+    assert_eq!(
+        stats
+            .skipped
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>(),
+        vec!["policies.csv:3: Duplicate source ids (test:1) of an earlier row"]
+    );
+}
@@ -0,0 +1,432 @@
+
+--
+-- Table structure for table `migrate_map_fedora_users`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_users`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_users` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_users`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_users`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_users` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_files`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_files`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_files` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `sourceid3` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191),`sourceid3`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_files`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_files`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_files` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_media`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_media`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_media` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_media`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_media`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_media` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_media_fields`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_media_fields`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_media_fields` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_media_fields`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_media_fields`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_media_fields` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_media_revisions`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_media_revisions`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_media_revisions` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `sourceid3` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191),`sourceid3`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_media_revisions`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_media_revisions`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_media_revisions` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_nodes`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_nodes`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_nodes` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_nodes`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_nodes`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_nodes` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Dumping data for table `users`
+--
+
+LOCK TABLES `users` WRITE;
+/*!40000 ALTER TABLE `users` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `users` (uid,uuid,langcode) VALUES
+  (2,'2be219ab-a7f7-5a91-a0c3-c417fe4f36b6','en'),
+  (3,'5049ba08-9908-5932-8520-215782fe044b','en');
+/*!40000 ALTER TABLE `users` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `users_field_data`
+--
+
+LOCK TABLES `users_field_data` WRITE;
+/*!40000 ALTER TABLE `users_field_data` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `users_field_data` (uid,langcode,name,created,access,login,default_langcode,mail,init,status,timezone) VALUES
+  (2,'en','alice',1600000000,0,0,1,'alice@example.com','alice@example.com',1,'UTC'),
+  (3,'en','bob',1600000000,0,0,1,'bob@example.com','bob@example.com',0,'America/Toronto');
+/*!40000 ALTER TABLE `users_field_data` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_users`
+--
+
+LOCK TABLES `migrate_map_fedora_users` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_users` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_users` (source_ids_hash,sourceid1,destid1,source_row_status,last_imported,hash) VALUES
+  ('40d774f14f837b20a7cdd3b2f52ea772b5ec73919c0bc9fe26cf31d86d547061','alice',2,0,1600000000,'7f4c1873e48a0ed8584044d3553f985acb923e9680132af915f86f7f7be40d36'),
+  ('d65ee0a73b2a0b2979013af1258eecd070198548141b11b93e607bbb7017b699','bob',3,0,1600000000,'1dc25418c8fea1a16a6bc1e6672df02305d7ea951f430bff159045b7361a07b2');
+/*!40000 ALTER TABLE `migrate_map_fedora_users` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node`
+--
+
+LOCK TABLES `node` WRITE;
+/*!40000 ALTER TABLE `node` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node` (nid,vid,type,uuid,langcode) VALUES
+  (1,1,'islandora_object','c9240663-36da-5b54-8ca6-3139aff08f4d','en'),
+  (2,2,'islandora_object','9c2e5370-0392-598a-92fd-6b7167b3271b','en'),
+  (3,3,'islandora_object','1cf9a75b-579f-5dcc-bc0c-62903781f99b','en');
+/*!40000 ALTER TABLE `node` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision`
+--
+
+LOCK TABLES `node_revision` WRITE;
+/*!40000 ALTER TABLE `node_revision` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision` (nid,vid,langcode,revision_uid,revision_timestamp,revision_default,revision_log) VALUES
+  (1,1,'en',1,1300000001,1,'Migrated from Fedora object test:root'),
+  (2,2,'en',2,1400000001,1,'Migrated from Fedora object test:1'),
+  (3,3,'en',3,1400000001,1,'Migrated from Fedora object test:2');
+/*!40000 ALTER TABLE `node_revision` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_field_data`
+--
+
+LOCK TABLES `node_field_data` WRITE;
+/*!40000 ALTER TABLE `node_field_data` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_field_data` (nid,vid,type,langcode,status,uid,title,created,changed,promote,sticky,default_langcode) VALUES
+  (1,1,'islandora_object','en',1,1,'Root',1300000000,1300000001,1,0,1),
+  (2,2,'islandora_object','en',1,2,'O\'Brien collection',1400000000,1400000001,1,0,1),
+  (3,3,'islandora_object','en',0,3,'Doc',1400000000,1400000001,1,0,1);
+/*!40000 ALTER TABLE `node_field_data` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_field_revision`
+--
+
+LOCK TABLES `node_field_revision` WRITE;
+/*!40000 ALTER TABLE `node_field_revision` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_field_revision` (nid,vid,langcode,status,uid,title,created,changed,promote,sticky,default_langcode) VALUES
+  (1,1,'en',1,1,'Root',1300000000,1300000001,1,0,1),
+  (2,2,'en',1,2,'O\'Brien collection',1400000000,1400000001,1,0,1),
+  (3,3,'en',0,3,'Doc',1400000000,1400000001,1,0,1);
+/*!40000 ALTER TABLE `node_field_revision` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node__field_member_of`
+--
+
+LOCK TABLES `node__field_member_of` WRITE;
+/*!40000 ALTER TABLE `node__field_member_of` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node__field_member_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_member_of_target_id) VALUES
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,1);
+/*!40000 ALTER TABLE `node__field_member_of` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision__field_member_of`
+--
+
+LOCK TABLES `node_revision__field_member_of` WRITE;
+/*!40000 ALTER TABLE `node_revision__field_member_of` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision__field_member_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_member_of_target_id) VALUES
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,1);
+/*!40000 ALTER TABLE `node_revision__field_member_of` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node__field_pid`
+--
+
+LOCK TABLES `node__field_pid` WRITE;
+/*!40000 ALTER TABLE `node__field_pid` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node__field_pid` (entity_id,revision_id,bundle,deleted,langcode,delta,field_pid_value) VALUES
+  (1,1,'islandora_object',0,'en',0,'test:root'),
+  (2,2,'islandora_object',0,'en',0,'test:1'),
+  (3,3,'islandora_object',0,'en',0,'test:2');
+/*!40000 ALTER TABLE `node__field_pid` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision__field_pid`
+--
+
+LOCK TABLES `node_revision__field_pid` WRITE;
+/*!40000 ALTER TABLE `node_revision__field_pid` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision__field_pid` (entity_id,revision_id,bundle,deleted,langcode,delta,field_pid_value) VALUES
+  (1,1,'islandora_object',0,'en',0,'test:root'),
+  (2,2,'islandora_object',0,'en',0,'test:1'),
+  (3,3,'islandora_object',0,'en',0,'test:2');
+/*!40000 ALTER TABLE `node_revision__field_pid` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node__field_weight`
+--
+
+LOCK TABLES `node__field_weight` WRITE;
+/*!40000 ALTER TABLE `node__field_weight` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node__field_weight` (entity_id,revision_id,bundle,deleted,langcode,delta,field_weight_value) VALUES
+  (1,1,'islandora_object',0,'en',0,0),
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,0);
+/*!40000 ALTER TABLE `node__field_weight` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision__field_weight`
+--
+
+LOCK TABLES `node_revision__field_weight` WRITE;
+/*!40000 ALTER TABLE `node_revision__field_weight` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision__field_weight` (entity_id,revision_id,bundle,deleted,langcode,delta,field_weight_value) VALUES
+  (1,1,'islandora_object',0,'en',0,0),
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,0);
+/*!40000 ALTER TABLE `node_revision__field_weight` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_nodes`
+--
+
+LOCK TABLES `migrate_map_fedora_nodes` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_nodes` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_nodes` (source_ids_hash,sourceid1,destid1,source_row_status,last_imported,hash) VALUES
+  ('452ac225326e3397ea74e50f11c69027a88535c81e88cba2593c91d2283dcc39','test:root',1,0,1600000000,'1042d17e8f41dffbd2a2bf5c53050773b40395e2a6ccb81d64b310b0886565c5'),
+  ('d87bf5efdd0976d4f2cb6505e94b6d7b090ff243859cdfcf296e7f5157f22b9f','test:1',2,0,1600000000,'0f7d93c56f86d10e09e8450328c30b9867421cbb0bdaf8017308d68ce5f8ee82'),
+  ('84b6dfe612bc8c89e5744cc0f4870a333a1fdc58c107b91a12dce0437dc495c1','test:2',3,0,1600000000,'5f88d3808125ffb9e18c09d50c7c496047187ec0c1968c5614a442d44da1fa0e');
+/*!40000 ALTER TABLE `migrate_map_fedora_nodes` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
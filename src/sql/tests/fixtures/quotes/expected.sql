@@ -0,0 +1,659 @@
+
+--
+-- Table structure for table `migrate_map_fedora_users`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_users`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_users` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_users`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_users`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_users` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_files`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_files`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_files` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `sourceid3` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191),`sourceid3`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_files`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_files`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_files` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_media`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_media`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_media` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_media`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_media`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_media` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_media_fields`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_media_fields`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_media_fields` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_media_fields`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_media_fields`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_media_fields` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_media_revisions`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_media_revisions`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_media_revisions` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `sourceid2` varchar(255) NOT NULL,
+    `sourceid3` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191),`sourceid2`(191),`sourceid3`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_media_revisions`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_media_revisions`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_media_revisions` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+
+--
+-- Table structure for table `migrate_map_fedora_nodes`
+--
+
+DROP TABLE IF EXISTS `migrate_map_fedora_nodes`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_map_fedora_nodes` (
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `sourceid1` varchar(255) NOT NULL,
+    `destid1` int(10) unsigned DEFAULT NULL,
+    `source_row_status` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Indicates current status of the source row',
+    `rollback_action` tinyint(3) unsigned NOT NULL DEFAULT 0 COMMENT 'Flag indicating what to do for this item on rollback',
+    `last_imported` int(10) unsigned NOT NULL DEFAULT 0 COMMENT 'UNIX timestamp of the last time this row was imported',
+    `hash` varchar(64) DEFAULT NULL COMMENT 'Hash of source row data, for detecting changes',
+    PRIMARY KEY (`source_ids_hash`),
+    KEY `source` (`sourceid1`(191))
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Mappings from source identifier value(s) to destination…';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Table structure for table `migrate_message_fedora_nodes`
+--
+
+DROP TABLE IF EXISTS `migrate_message_fedora_nodes`;
+/*!40101 SET @saved_cs_client     = @@character_set_client */;
+/*!40101 SET character_set_client = utf8 */;
+CREATE TABLE `migrate_message_fedora_nodes` (
+    `msgid` int(10) unsigned NOT NULL AUTO_INCREMENT,
+    `source_ids_hash` varchar(64) NOT NULL COMMENT 'Hash of source ids. Used as primary key',
+    `level` int(10) unsigned NOT NULL DEFAULT 1,
+    `message` mediumtext NOT NULL,
+    PRIMARY KEY (`msgid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='Messages generated during a migration process';
+/*!40101 SET character_set_client = @saved_cs_client */;
+
+--
+-- Dumping data for table `users`
+--
+
+LOCK TABLES `users` WRITE;
+/*!40000 ALTER TABLE `users` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `users` (uid,uuid,langcode) VALUES
+  (2,'2be219ab-a7f7-5a91-a0c3-c417fe4f36b6','en'),
+  (3,'5049ba08-9908-5932-8520-215782fe044b','en');
+/*!40000 ALTER TABLE `users` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `users_field_data`
+--
+
+LOCK TABLES `users_field_data` WRITE;
+/*!40000 ALTER TABLE `users_field_data` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `users_field_data` (uid,langcode,name,created,access,login,default_langcode,mail,init,status,timezone) VALUES
+  (2,'en','alice',1600000000,0,0,1,'alice@example.com','alice@example.com',1,'UTC'),
+  (3,'en','bob',1600000000,0,0,1,'bob@example.com','bob@example.com',0,'America/Toronto');
+/*!40000 ALTER TABLE `users_field_data` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_users`
+--
+
+LOCK TABLES `migrate_map_fedora_users` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_users` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_users` (source_ids_hash,sourceid1,destid1,source_row_status,last_imported,hash) VALUES
+  ('40d774f14f837b20a7cdd3b2f52ea772b5ec73919c0bc9fe26cf31d86d547061','alice',2,0,1600000000,'7f4c1873e48a0ed8584044d3553f985acb923e9680132af915f86f7f7be40d36'),
+  ('d65ee0a73b2a0b2979013af1258eecd070198548141b11b93e607bbb7017b699','bob',3,0,1600000000,'1dc25418c8fea1a16a6bc1e6672df02305d7ea951f430bff159045b7361a07b2');
+/*!40000 ALTER TABLE `migrate_map_fedora_users` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `file_managed`
+--
+
+LOCK TABLES `file_managed` WRITE;
+/*!40000 ALTER TABLE `file_managed` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `file_managed` (fid,uuid,langcode,uid,filename,uri,filemime,filesize,status,created,changed) VALUES
+  (1,'6e637a47-b796-5cf7-ab35-361f2377872c','en',2,'a.jpg','public://fedora/a.jpg','image/jpeg',100,1,1400000000,1600000000),
+  (2,'ed75dd5c-930a-5279-8452-44f2148ef793','en',2,'b.jpg','public://fedora/b.jpg','image/jpeg',200,1,1400000100,1600000000),
+  (3,'59ba5a58-857d-5392-8c78-8a83420b3085','en',1,'O\'Brien.pdf','public://fedora/c.pdf','application/pdf',300,1,1400000200,1600000000);
+/*!40000 ALTER TABLE `file_managed` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `filehash`
+--
+
+LOCK TABLES `filehash` WRITE;
+/*!40000 ALTER TABLE `filehash` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `filehash` (fid,sha1) VALUES
+  (1,'abc'),
+  (2,'def'),
+  (3,'ghi');
+/*!40000 ALTER TABLE `filehash` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_files`
+--
+
+LOCK TABLES `migrate_map_fedora_files` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_files` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_files` (source_ids_hash,sourceid1,sourceid2,sourceid3,destid1,source_row_status,last_imported,hash) VALUES
+  ('f83d359224c74dbde01c7ddb26ebd55a36611171c13af38513fe4bcb7ce54d12','test:1','OBJ','OBJ.0',1,0,1600000000,'c0b6684aa7888e06edb87560c423ce21a3880e013e69a3c11d771d0c28e43777'),
+  ('0bb342108cb8e0f196fa8be6054c474cf6bb08aca5fefad9cd5191dc869c9109','test:1','OBJ','OBJ.1',2,0,1600000000,'0d04e43f5929d36fb0f46d8611905d2632dd7a46fa7bdc817f27712241e57ed8'),
+  ('604c21596b00914457b8765bd1a35284f7bd5fb2bc9d1da5a786d47219e34bbd','test:2','PDF','PDF.0',3,0,1600000000,'0a278facab3782e403d449fb5c94489e3aafc50b420831e10e14732caf43842c');
+/*!40000 ALTER TABLE `migrate_map_fedora_files` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node`
+--
+
+LOCK TABLES `node` WRITE;
+/*!40000 ALTER TABLE `node` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node` (nid,vid,type,uuid,langcode) VALUES
+  (1,1,'islandora_object','c9240663-36da-5b54-8ca6-3139aff08f4d','en'),
+  (2,2,'islandora_object','9c2e5370-0392-598a-92fd-6b7167b3271b','en'),
+  (3,3,'islandora_object','1cf9a75b-579f-5dcc-bc0c-62903781f99b','en');
+/*!40000 ALTER TABLE `node` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision`
+--
+
+LOCK TABLES `node_revision` WRITE;
+/*!40000 ALTER TABLE `node_revision` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision` (nid,vid,langcode,revision_uid,revision_timestamp,revision_default,revision_log) VALUES
+  (1,1,'en',1,1300000001,1,'Migrated from Fedora object test:root'),
+  (2,2,'en',2,1400000001,1,'Migrated from Fedora object test:1'),
+  (3,3,'en',3,1400000001,1,'Migrated from Fedora object test:2');
+/*!40000 ALTER TABLE `node_revision` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_field_data`
+--
+
+LOCK TABLES `node_field_data` WRITE;
+/*!40000 ALTER TABLE `node_field_data` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_field_data` (nid,vid,type,langcode,status,uid,title,created,changed,promote,sticky,default_langcode) VALUES
+  (1,1,'islandora_object','en',1,1,'Root',1300000000,1300000001,1,0,1),
+  (2,2,'islandora_object','en',1,2,'O\'Brien collection',1400000000,1400000001,1,0,1),
+  (3,3,'islandora_object','en',0,3,'A "quoted" back\\slash',1400000000,1400000001,1,0,1);
+/*!40000 ALTER TABLE `node_field_data` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_field_revision`
+--
+
+LOCK TABLES `node_field_revision` WRITE;
+/*!40000 ALTER TABLE `node_field_revision` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_field_revision` (nid,vid,langcode,status,uid,title,created,changed,promote,sticky,default_langcode) VALUES
+  (1,1,'en',1,1,'Root',1300000000,1300000001,1,0,1),
+  (2,2,'en',1,2,'O\'Brien collection',1400000000,1400000001,1,0,1),
+  (3,3,'en',0,3,'A "quoted" back\\slash',1400000000,1400000001,1,0,1);
+/*!40000 ALTER TABLE `node_field_revision` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node__field_member_of`
+--
+
+LOCK TABLES `node__field_member_of` WRITE;
+/*!40000 ALTER TABLE `node__field_member_of` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node__field_member_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_member_of_target_id) VALUES
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,1);
+/*!40000 ALTER TABLE `node__field_member_of` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision__field_member_of`
+--
+
+LOCK TABLES `node_revision__field_member_of` WRITE;
+/*!40000 ALTER TABLE `node_revision__field_member_of` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision__field_member_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_member_of_target_id) VALUES
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,1);
+/*!40000 ALTER TABLE `node_revision__field_member_of` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node__field_pid`
+--
+
+LOCK TABLES `node__field_pid` WRITE;
+/*!40000 ALTER TABLE `node__field_pid` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node__field_pid` (entity_id,revision_id,bundle,deleted,langcode,delta,field_pid_value) VALUES
+  (1,1,'islandora_object',0,'en',0,'test:root'),
+  (2,2,'islandora_object',0,'en',0,'test:1'),
+  (3,3,'islandora_object',0,'en',0,'test:2');
+/*!40000 ALTER TABLE `node__field_pid` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision__field_pid`
+--
+
+LOCK TABLES `node_revision__field_pid` WRITE;
+/*!40000 ALTER TABLE `node_revision__field_pid` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision__field_pid` (entity_id,revision_id,bundle,deleted,langcode,delta,field_pid_value) VALUES
+  (1,1,'islandora_object',0,'en',0,'test:root'),
+  (2,2,'islandora_object',0,'en',0,'test:1'),
+  (3,3,'islandora_object',0,'en',0,'test:2');
+/*!40000 ALTER TABLE `node_revision__field_pid` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node__field_weight`
+--
+
+LOCK TABLES `node__field_weight` WRITE;
+/*!40000 ALTER TABLE `node__field_weight` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node__field_weight` (entity_id,revision_id,bundle,deleted,langcode,delta,field_weight_value) VALUES
+  (1,1,'islandora_object',0,'en',0,0),
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,0);
+/*!40000 ALTER TABLE `node__field_weight` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `node_revision__field_weight`
+--
+
+LOCK TABLES `node_revision__field_weight` WRITE;
+/*!40000 ALTER TABLE `node_revision__field_weight` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `node_revision__field_weight` (entity_id,revision_id,bundle,deleted,langcode,delta,field_weight_value) VALUES
+  (1,1,'islandora_object',0,'en',0,0),
+  (2,2,'islandora_object',0,'en',0,1),
+  (3,3,'islandora_object',0,'en',0,0);
+/*!40000 ALTER TABLE `node_revision__field_weight` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_nodes`
+--
+
+LOCK TABLES `migrate_map_fedora_nodes` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_nodes` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_nodes` (source_ids_hash,sourceid1,destid1,source_row_status,last_imported,hash) VALUES
+  ('452ac225326e3397ea74e50f11c69027a88535c81e88cba2593c91d2283dcc39','test:root',1,0,1600000000,'1042d17e8f41dffbd2a2bf5c53050773b40395e2a6ccb81d64b310b0886565c5'),
+  ('d87bf5efdd0976d4f2cb6505e94b6d7b090ff243859cdfcf296e7f5157f22b9f','test:1',2,0,1600000000,'0f7d93c56f86d10e09e8450328c30b9867421cbb0bdaf8017308d68ce5f8ee82'),
+  ('84b6dfe612bc8c89e5744cc0f4870a333a1fdc58c107b91a12dce0437dc495c1','test:2',3,0,1600000000,'e227e16f941999613ad6f174ba8086789d59ff416b376196c53f46c7c6851fb9');
+/*!40000 ALTER TABLE `migrate_map_fedora_nodes` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media`
+--
+
+LOCK TABLES `media` WRITE;
+/*!40000 ALTER TABLE `media` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media` (mid,vid,bundle,uuid,langcode) VALUES
+  (1,1,'image','033ee882-393f-5424-a617-f27ec6ee5f35','en'),
+  (2,2,'document','1b8140a5-aa5c-5ab4-abc2-b943516ea901','en');
+/*!40000 ALTER TABLE `media` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media_field_data`
+--
+
+LOCK TABLES `media_field_data` WRITE;
+/*!40000 ALTER TABLE `media_field_data` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media_field_data` (mid,vid,bundle,langcode,status,uid,name,created,changed,default_langcode) VALUES
+  (1,1,'image','en',1,2,'b.jpg',1400000100,1400000100, 1),
+  (2,2,'document','en',1,1,'O\'Brien.pdf',1400000200,1400000200, 1);
+/*!40000 ALTER TABLE `media_field_data` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media__field_media_file`
+--
+
+LOCK TABLES `media__field_media_file` WRITE;
+/*!40000 ALTER TABLE `media__field_media_file` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media__field_media_file` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_file_target_id) VALUES
+  (2,2,'document',0,'en',0,3);
+/*!40000 ALTER TABLE `media__field_media_file` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media__field_media_image`
+--
+
+LOCK TABLES `media__field_media_image` WRITE;
+/*!40000 ALTER TABLE `media__field_media_image` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media__field_media_image` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_image_target_id) VALUES
+  (1,1,'image',0,'en',0,2);
+/*!40000 ALTER TABLE `media__field_media_image` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media__field_media_of`
+--
+
+LOCK TABLES `media__field_media_of` WRITE;
+/*!40000 ALTER TABLE `media__field_media_of` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media__field_media_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_of_target_id) VALUES
+  (1,1,'image',0,'en',0,2),
+  (2,2,'document',0,'en',0,3);
+/*!40000 ALTER TABLE `media__field_media_of` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_media`
+--
+
+LOCK TABLES `migrate_map_fedora_media` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_media` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_media` (source_ids_hash,sourceid1,sourceid2,destid1,source_row_status,last_imported,hash) VALUES
+  ('7e09b5affc28cd9fb24c6038763f2f31568509bed63926064398c4f75297b0cf','test:1','OBJ',1,0,1600000000,'3e5100c005bb5bfc08f211df19c59fdd5e93521f927b8a471629198132f2b4b8'),
+  ('3386f406dd66a66401f94582a538a485457a9e5d118ff7934e6015d664e3d0fc','test:2','PDF',2,0,1600000000,'eee9e2f3363681b8b7a4f396afd980374b50d2c825eb59a43b93cea6709a67eb');
+/*!40000 ALTER TABLE `migrate_map_fedora_media` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media_revision`
+--
+
+LOCK TABLES `media_revision` WRITE;
+/*!40000 ALTER TABLE `media_revision` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media_revision` (mid,vid,langcode,revision_user,revision_created,revision_default,revision_log_message) VALUES
+  (1,1,'en',2,1400000100,1,'Migrated from Fedora version OBJ.1'),
+  (2,2,'en',1,1400000200,1,'Migrated from Fedora version PDF.0'),
+  (1,3,'en',2,1400000000,1,'Migrated from Fedora version OBJ.0');
+/*!40000 ALTER TABLE `media_revision` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media_field_revision`
+--
+
+LOCK TABLES `media_field_revision` WRITE;
+/*!40000 ALTER TABLE `media_field_revision` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media_field_revision` (mid,vid,langcode,status,uid,name,created,changed,default_langcode) VALUES
+  (1,1,'en',1,2,'b.jpg',1400000100,1400000100, 1),
+  (2,2,'en',1,1,'O\'Brien.pdf',1400000200,1400000200, 1),
+  (1,3,'en',1,2,'a.jpg',1400000000,1400000000, 1);
+/*!40000 ALTER TABLE `media_field_revision` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `file_usage`
+--
+
+LOCK TABLES `file_usage` WRITE;
+/*!40000 ALTER TABLE `file_usage` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `file_usage` (fid,module,type,id,count) VALUES
+  (2,'file','media',1,1),
+  (3,'file','media',2,1),
+  (1,'file','media',1,1);
+/*!40000 ALTER TABLE `file_usage` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media_revision__field_media_file`
+--
+
+LOCK TABLES `media_revision__field_media_file` WRITE;
+/*!40000 ALTER TABLE `media_revision__field_media_file` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media_revision__field_media_file` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_file_target_id) VALUES
+  (2,2,'document',0,'en',0,3);
+/*!40000 ALTER TABLE `media_revision__field_media_file` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media_revision__field_media_image`
+--
+
+LOCK TABLES `media_revision__field_media_image` WRITE;
+/*!40000 ALTER TABLE `media_revision__field_media_image` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media_revision__field_media_image` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_image_target_id) VALUES
+  (1,1,'image',0,'en',0,2),
+  (1,3,'image',0,'en',0,1);
+/*!40000 ALTER TABLE `media_revision__field_media_image` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `media_revision__field_media_of`
+--
+
+LOCK TABLES `media_revision__field_media_of` WRITE;
+/*!40000 ALTER TABLE `media_revision__field_media_of` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `media_revision__field_media_of` (entity_id,revision_id,bundle,deleted,langcode,delta,field_media_of_target_id) VALUES
+  (1,1,'image',0,'en',0,2),
+  (2,2,'document',0,'en',0,3),
+  (1,3,'image',0,'en',0,2);
+/*!40000 ALTER TABLE `media_revision__field_media_of` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
+
+--
+-- Dumping data for table `migrate_map_fedora_media_revisions`
+--
+
+LOCK TABLES `migrate_map_fedora_media_revisions` WRITE;
+/*!40000 ALTER TABLE `migrate_map_fedora_media_revisions` DISABLE KEYS */;
+set autocommit=0;
+INSERT INTO `migrate_map_fedora_media_revisions` (source_ids_hash,sourceid1,sourceid2,sourceid3,destid1,source_row_status,last_imported,hash) VALUES
+  ('f83d359224c74dbde01c7ddb26ebd55a36611171c13af38513fe4bcb7ce54d12','test:1','OBJ','OBJ.0',3,0,1600000000,'18a3e75c0902377a7c3fda8b4f6322b1d0a5fa41aa755209846ecba0d1ce2c36');
+/*!40000 ALTER TABLE `migrate_map_fedora_media_revisions` ENABLE KEYS */;
+UNLOCK TABLES;
+commit;
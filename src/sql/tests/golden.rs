@@ -0,0 +1,60 @@
+// Compares the SQL generated for each directory in tests/fixtures with the
+// expected.sql committed alongside it. After an intended change to the output
+// regenerate them with:
+//
+//   UPDATE_GOLDEN=1 cargo test -p sql --test golden
+use sql::MigrationBuilder;
+use std::fs;
+use std::path::Path;
+
+fn generate(input: &Path) -> String {
+    MigrationBuilder::new(input)
+        .deterministic_uuids(true)
+        .generated_at(1600000000)
+        .build()
+        .unwrap()
+        .to_sql_string()
+        .unwrap_or_else(|error| panic!("{}: {}", input.display(), error))
+}
+
+#[test]
+fn golden() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut inputs = fs::read_dir(&fixtures)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    inputs.sort();
+    assert!(!inputs.is_empty());
+    for input in inputs {
+        let sql = generate(&input);
+        let golden = input.join("expected.sql");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&golden, &sql).unwrap();
+            continue;
+        }
+        let expected = fs::read_to_string(&golden)
+            .unwrap_or_else(|error| panic!("{}: {}", golden.display(), error));
+        // Report the first line which differs rather than the whole output.
+        if let Some((line, (actual, expected))) = sql
+            .lines()
+            .zip(expected.lines())
+            .enumerate()
+            .find(|(_, (actual, expected))| actual != expected)
+        {
+            panic!(
+                "{}:{} differs\n  expected: {}\n    actual: {}",
+                golden.display(),
+                line + 1,
+                expected,
+                actual
+            );
+        }
+        assert_eq!(
+            sql.lines().count(),
+            expected.lines().count(),
+            "{} has a different number of lines",
+            golden.display()
+        );
+    }
+}
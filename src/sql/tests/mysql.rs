@@ -0,0 +1,236 @@
+// Loads the generated SQL into a MySQL container, which catches escaping,
+// quoting, column type and empty table mistakes the unit tests can not.
+// Requires the mysql feature and Docker:
+//
+//   cargo test -p sql --features mysql --test mysql
+#![cfg(feature = "mysql")]
+
+use indexmap::IndexMap;
+use mysql::prelude::Queryable;
+use sql::MigrationBuilder;
+use std::fs;
+use std::path::Path;
+use testcontainers_modules::{mysql::Mysql, testcontainers::runners::SyncRunner};
+
+fn input_directory() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+    let media = "pid,dsid,version,bundle,created_date,file_size,label,mime_type,name,user\n";
+    write(
+        "users.csv",
+        "name,pass,mail,status,timezone,language\n\
+         alice,secret,alice@example.com,1,UTC,en\n\
+         bob,,bob@example.com,0,America/Toronto,fr\n",
+    );
+    write(
+        "files.csv",
+        "pid,dsid,version,created_date,mime_type,name,path,user,sha1,size\n\
+         test:1,OBJ,OBJ.0,1400000000,image/jpeg,a.jpg,public://fedora/a.jpg,alice,abc,100\n\
+         test:2,PDF,PDF.0,1400000200,application/pdf,O'Brien.pdf,public://fedora/c.pdf,admin,ghi,300\n",
+    );
+    write(
+        "media.csv",
+        &format!(
+            "{}test:1,OBJ,OBJ.0,image,1400000000,100,Label,image/jpeg,a.jpg,alice\n\
+             test:2,PDF,PDF.0,document,1400000200,300,PDF,application/pdf,O'Brien.pdf,admin\n",
+            media
+        ),
+    );
+    // No previous versions, so the revision tables have no rows.
+    write("media_revisions.csv", media);
+    write(
+        "nodes.csv",
+        "pid,created_date,label,weight,model,modified_date,state,user,display_hint,parents\n\
+         test:root,1300000000,Root,,http://purl.org/dc/dcmitype/Collection,1300000001,Active,admin,,\n\
+         test:1,1400000000,\"O'Brien \"\"quoted\"\" back\\slash\",1,http://purl.org/coar/resource_type/c_c513,1400000001,Active,alice,,test:root\n\
+         test:2,1400000000,Doc,,https://schema.org/DigitalDocument,1400000001,Inactive,bob,,test:root\n",
+    );
+    dir
+}
+
+// Drupal's own schema for the tables whose column types the generated values
+// have to fit, rather than just be parsed into.
+const DRUPAL_TABLES: &[(&str, &str)] = &[
+    (
+        "file_managed",
+        "`fid` int unsigned NOT NULL AUTO_INCREMENT,
+         `uuid` varchar(128) CHARACTER SET ascii NOT NULL,
+         `langcode` varchar(12) CHARACTER SET ascii NOT NULL,
+         `uid` int unsigned DEFAULT NULL,
+         `filename` varchar(255) DEFAULT NULL,
+         `uri` varchar(255) CHARACTER SET ascii COLLATE ascii_bin NOT NULL,
+         `filemime` varchar(255) CHARACTER SET ascii DEFAULT NULL,
+         `filesize` bigint unsigned DEFAULT NULL,
+         `status` tinyint NOT NULL,
+         `created` int DEFAULT NULL,
+         `changed` int NOT NULL,
+         PRIMARY KEY (`fid`),
+         UNIQUE KEY `file_field__uuid__value` (`uuid`)",
+    ),
+    (
+        "media",
+        "`mid` int unsigned NOT NULL AUTO_INCREMENT,
+         `vid` int unsigned DEFAULT NULL,
+         `bundle` varchar(32) CHARACTER SET ascii NOT NULL,
+         `uuid` varchar(128) CHARACTER SET ascii NOT NULL,
+         `langcode` varchar(12) CHARACTER SET ascii NOT NULL,
+         PRIMARY KEY (`mid`),
+         UNIQUE KEY `media_field__uuid__value` (`uuid`),
+         UNIQUE KEY `media__revision_id` (`vid`)",
+    ),
+    (
+        "media_field_data",
+        "`mid` int unsigned NOT NULL,
+         `vid` int unsigned NOT NULL,
+         `bundle` varchar(32) CHARACTER SET ascii NOT NULL,
+         `langcode` varchar(12) CHARACTER SET ascii NOT NULL,
+         `status` tinyint NOT NULL,
+         `uid` int unsigned DEFAULT NULL,
+         `name` varchar(255) DEFAULT NULL,
+         `thumbnail__target_id` int unsigned DEFAULT NULL,
+         `created` int DEFAULT NULL,
+         `changed` int DEFAULT NULL,
+         `default_langcode` tinyint NOT NULL,
+         `revision_translation_affected` tinyint DEFAULT NULL,
+         PRIMARY KEY (`mid`,`langcode`)",
+    ),
+];
+
+// The generated SQL creates the migration mapping tables itself, with the
+// types Drupal gives them, but expects the Drupal tables to exist already.
+// Besides those in DRUPAL_TABLES, stand in for them with tables of text
+// columns named after those inserted into.
+fn create_drupal_tables(conn: &mut mysql::Conn, sql: &str) {
+    let mut tables: IndexMap<&str, String> = IndexMap::new();
+    for line in sql.lines() {
+        if let Some(rest) = line.strip_prefix("INSERT INTO `") {
+            let (table, rest) = rest.split_once('`').unwrap();
+            let columns = rest.split('(').nth(1).unwrap().split(')').next().unwrap();
+            let columns = columns
+                .split(',')
+                .map(|column| format!("`{}` longtext", column))
+                .collect::<Vec<_>>()
+                .join(", ");
+            tables.insert(table, columns);
+        }
+    }
+    for (table, columns) in DRUPAL_TABLES {
+        tables.insert(table, columns.to_string());
+    }
+    for (table, columns) in tables {
+        if table.starts_with("migrate_") {
+            continue;
+        }
+        conn.query_drop(format!(
+            "CREATE TABLE `{}` ({}) DEFAULT CHARSET=utf8mb4",
+            table, columns
+        ))
+        .unwrap_or_else(|error| panic!("{}: {}", table, error));
+    }
+}
+
+fn query<T: mysql::prelude::FromRow>(conn: &mut mysql::Conn, query: &str) -> Vec<T> {
+    conn.query(query)
+        .unwrap_or_else(|error| panic!("{}: {}", query, error))
+}
+
+#[test]
+fn load_into_mysql() {
+    let input = input_directory();
+    let output = tempfile::tempdir().unwrap();
+    let stats = MigrationBuilder::new(input.path())
+        .deterministic_uuids(true)
+        .generated_at(1600000000)
+        .build()
+        .unwrap()
+        .write_to(output.path())
+        .unwrap();
+    let sql = fs::read_to_string(Path::new(output.path()).join("migrate.sql")).unwrap();
+
+    let container = Mysql::default().start().unwrap();
+    let url = format!(
+        "mysql://root@{}:{}/test",
+        container.get_host().unwrap(),
+        container.get_host_port_ipv4(3306).unwrap()
+    );
+    let mut conn = mysql::Conn::new(mysql::Opts::from_url(&url).unwrap()).unwrap();
+    create_drupal_tables(&mut conn, &sql);
+    conn.query_drop(&sql).unwrap();
+
+    let count = |conn: &mut mysql::Conn, table: &str| -> usize {
+        query(conn, &format!("SELECT COUNT(*) FROM `{}`", table))[0]
+    };
+    assert_eq!(count(&mut conn, "file_managed"), 2);
+    assert_eq!(count(&mut conn, "media"), 2);
+    assert_eq!(count(&mut conn, "node"), 3);
+    assert_eq!(count(&mut conn, "migrate_map_fedora_files"), 2);
+    assert_eq!(count(&mut conn, "migrate_map_fedora_media"), 2);
+    assert_eq!(count(&mut conn, "migrate_map_fedora_media_revisions"), 0);
+    assert_eq!(count(&mut conn, "migrate_map_fedora_nodes"), 3);
+    assert_eq!(count(&mut conn, "migrate_map_fedora_users"), 2);
+    assert_eq!(stats.entities["node"], 3);
+
+    assert_eq!(
+        query::<(String, String)>(
+            &mut conn,
+            "SELECT filename, filemime FROM file_managed ORDER BY fid"
+        ),
+        vec![
+            ("a.jpg".to_string(), "image/jpeg".to_string()),
+            ("O'Brien.pdf".to_string(), "application/pdf".to_string()),
+        ]
+    );
+    assert_eq!(
+        query::<(String, String)>(
+            &mut conn,
+            "SELECT bundle, name FROM media_field_data ORDER BY mid"
+        ),
+        vec![
+            ("image".to_string(), "a.jpg".to_string()),
+            ("document".to_string(), "O'Brien.pdf".to_string()),
+        ]
+    );
+    assert_eq!(
+        query::<String>(
+            &mut conn,
+            "SELECT title FROM node_field_data ORDER BY CAST(nid AS UNSIGNED)"
+        ),
+        vec![
+            "Root".to_string(),
+            r#"O'Brien "quoted" back\slash"#.to_string(),
+            "Doc".to_string(),
+        ]
+    );
+    // Each mapping refers to the destination id written for its source row.
+    assert_eq!(
+        query::<(String, usize, u8)>(
+            &mut conn,
+            "SELECT sourceid1, destid1, source_row_status FROM migrate_map_fedora_files ORDER BY destid1"
+        ),
+        vec![
+            ("test:1".to_string(), 1, sql::MIGRATE_STATUS_IMPORTED),
+            ("test:2".to_string(), 2, sql::MIGRATE_STATUS_IMPORTED),
+        ]
+    );
+    assert_eq!(
+        query::<usize>(
+            &mut conn,
+            "SELECT fid FROM file_managed WHERE fid IN (SELECT destid1 FROM migrate_map_fedora_files)"
+        )
+        .len(),
+        2
+    );
+    // The mapping tables were created by the generated SQL, not stood in for.
+    assert_eq!(
+        query::<(String, String)>(
+            &mut conn,
+            "SELECT COLUMN_NAME, DATA_TYPE FROM information_schema.COLUMNS \
+             WHERE TABLE_SCHEMA = 'test' AND TABLE_NAME = 'migrate_map_fedora_files' \
+             AND COLUMN_NAME IN ('destid1', 'source_row_status') ORDER BY COLUMN_NAME"
+        ),
+        vec![
+            ("destid1".to_string(), "int".to_string()),
+            ("source_row_status".to_string(), "tinyint".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,219 @@
+use crate::common::*;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+pub(crate) struct UserRow {
+    pub(crate) name: String,
+    pub(crate) pass: String,
+    pub(crate) mail: String,
+    pub(crate) status: String,
+    pub(crate) timezone: String,
+    pub(crate) language: String,
+    #[serde(default)]
+    pub(crate) login: Option<String>,
+    #[serde(default)]
+    pub(crate) init: Option<String>,
+    #[serde(default)]
+    pub(crate) roles: Option<String>,
+    // The name users are looked up by, see MigrationConfig::normalize_user().
+    #[serde(skip)]
+    pub(crate) source_id: String,
+}
+
+impl UserRow {
+    // Users without a status are active.
+    pub(crate) fn status(&self) -> Result<u8> {
+        match self.status.as_str() {
+            "" | "1" => Ok(1),
+            "0" => Ok(0),
+            _ => Err(Error::InvalidStatus {
+                name: self.name.clone(),
+                status: self.status.clone(),
+            }),
+        }
+    }
+
+    // Users without a login column, or an empty one, have never logged in.
+    pub(crate) fn login(&self, config: &MigrationConfig) -> Result<String> {
+        match self.login.as_deref() {
+            None | Some("") => Ok("0".to_string()),
+            Some(login) => config.timestamp(&self.name, login),
+        }
+    }
+
+    // The mail the account was created with, the current mail unless given.
+    pub(crate) fn init(&self) -> &str {
+        match self.init.as_deref() {
+            None | Some("") => &self.mail,
+            Some(init) => init,
+        }
+    }
+
+    // Machine names of the roles besides authenticated, which every user has.
+    pub(crate) fn roles(&self, config: &MigrationConfig) -> impl Iterator<Item = &str> {
+        self.roles
+            .as_deref()
+            .unwrap_or_default()
+            .split(config.role_delimiter)
+            .map(|role| role.trim())
+            .filter(|role| !role.is_empty())
+    }
+
+    // Value of the pass column if passwords are migrated, empty if the user has none.
+    // Plaintext passwords are salted randomly, never from the user or the uuid
+    // options, so the hashes can not be precomputed.
+    pub(crate) fn pass(&self, config: &MigrationConfig) -> Option<String> {
+        match config.passwords {
+            Passwords::Omit => None,
+            _ if self.pass.is_empty() => Some(String::new()),
+            Passwords::Plaintext => {
+                let salt: [u8; 6] = rand::random();
+                Some(password_hash(&self.pass, &salt))
+            }
+            Passwords::Hashed => Some(self.pass.clone()),
+        }
+    }
+}
+
+impl SourceRow for UserRow {
+    fn id() -> IdMaps {
+        IdMaps::UID
+    }
+
+    fn offset(offsets: &Offsets) -> usize {
+        offsets.users
+    }
+
+    fn file_name(names: &FileNames) -> &str {
+        &names.users
+    }
+
+    fn take(files: &mut ParsedFiles) -> Rows<Self> {
+        std::mem::take(&mut files.users)
+    }
+
+    fn headers() -> &'static [&'static str] {
+        &["name", "pass", "mail", "status", "timezone", "language"]
+    }
+
+    fn optional_headers() -> &'static [&'static str] {
+        &["login", "init", "roles"]
+    }
+
+    fn complete(&mut self, config: &MigrationConfig) -> Result<()> {
+        self.source_id = self.name.clone();
+        config.normalize_user(&mut self.source_id);
+        Ok(())
+    }
+
+    fn source_ids(&self) -> Vec<&str> {
+        vec![self.source_id.as_str()]
+    }
+}
+
+pub(crate) type MigrateUserMap = MigrateMap<UserRow>;
+
+impl TableSerializer for MigrateUserMap {
+    fn tables<'a>(&'a self, config: &'a MigrationConfig) -> Vec<Table<'a>> {
+        let mut tables = vec![
+            Table {
+                name: "users".to_string(),
+                columns: columns(&["uid", "uuid", "langcode"]),
+                values: self.values(config, move |(index, (hash, _))| {
+                    let uuid = uuid("user", hash, config);
+                    Ok(format!(
+                        "({},'{}',{})",
+                        index,
+                        uuid,
+                        config.dialect.quote(&config.langcode)
+                    ))
+                }),
+            },
+            Table {
+                name: "users_field_data".to_string(),
+                columns: {
+                    let mut columns = columns(&[
+                        "uid",
+                        "langcode",
+                        "name",
+                        "created",
+                        "access",
+                        "login",
+                        "default_langcode",
+                        "mail",
+                        "init",
+                        "status",
+                        "timezone",
+                    ]);
+                    if config.passwords != Passwords::Omit {
+                        columns.push("pass".to_string());
+                    }
+                    columns
+                },
+                values: self.values(config, move |(index, (_, user))| {
+                    let pass = match user.pass(config) {
+                        None => String::new(),
+                        Some(pass) if pass.is_empty() => ",NULL".to_string(),
+                        Some(pass) => format!(",{}", config.dialect.quote(&pass)),
+                    };
+                    // The last access is at least the last login.
+                    let login = user.login(config)?;
+                    Ok(format!(
+                        "({},{},{},{},{},{},1,{},{},{},{}{})",
+                        index,
+                        config.dialect.quote(&config.langcode),
+                        config.dialect.quote(&user.name),
+                        config.now(),
+                        login,
+                        login,
+                        config.dialect.nullable(&user.mail),
+                        config.dialect.nullable(user.init()),
+                        user.status()?,
+                        config.dialect.quote(&user.timezone),
+                        pass
+                    ))
+                }),
+            },
+            Table {
+                name: "user__roles".to_string(),
+                columns: columns(&[
+                    "entity_id",
+                    "revision_id",
+                    "bundle",
+                    "deleted",
+                    "langcode",
+                    "delta",
+                    "roles_target_id",
+                ]),
+                values: Box::new(self.rows(config).flat_map(move |(index, (_, user))| {
+                    user.roles(config).enumerate().map(move |(delta, role)| {
+                        Ok(format!(
+                            "({},{},'user',0,{},{},{})",
+                            index,
+                            index,
+                            config.dialect.quote(&config.langcode),
+                            delta,
+                            config.dialect.quote(role)
+                        ))
+                    })
+                })),
+            },
+        ];
+        if config.emit_map_tables {
+            tables.push(Table {
+                name: config.map_table("users"),
+                columns: columns(&[
+                    "source_ids_hash",
+                    "sourceid1",
+                    "destid1",
+                    "source_row_status",
+                    "last_imported",
+                    "hash",
+                ]),
+                values: self.migrate_map_values(config),
+            });
+        }
+        tables
+    }
+}